@@ -1,67 +1,189 @@
 use crate::{
-    crafting::Part,
+    crafting::{CraftingAssets, Item, Part},
+    ground_items::drop_part,
+    placement::AddPlaceable,
     player::{Player, PlayerInteractVolume},
-    ui::text_log::ItemLogEvent,
+    traps::fire::Flammable,
+    ui::{offscreen_indicator::OffscreenTarget, text_log::ItemLogEvent},
+    zombies::NoiseEvent,
     GameState, Score,
 };
 use bevy::log::*;
 use bevy_godot::prelude::{
-    bevy_prelude::{Added, EventWriter, With, Without},
-    godot_prelude::Vector2,
+    bevy_prelude::{Added, Changed, EventWriter, Or, With, Without},
+    godot_prelude::{Color, Vector2},
     *,
 };
 use iyes_loopless::prelude::*;
 use std::f32::consts::PI;
 
+// How many parts fit in a crate
+pub const CRATE_CAPACITY: usize = 5;
+// Bolts the army packs into every crate
+const CRATE_BOLTS: u32 = 25;
+// Noise level of opening a crate (in dB at 1m)
+const OPEN_CRATE_NOISE: f32 = 50.0;
+// Wood pieces left after taking a crate apart
+const CRATE_WOOD: usize = 2;
+// How long a crate hangs on the parachute (in seconds)
+const DESCENT_TIME: f32 = 8.0;
+// How high above the ground a crate starts its descent
+const DESCENT_HEIGHT: f32 = 600.0;
+// Noise level of a crate hitting the ground (in dB at 1m)
+const LANDING_NOISE: f32 = 90.0;
+
 pub struct AirDropsPlugin;
 impl Plugin for AirDropsPlugin {
     fn build(&self, app: &mut App) {
-        // start with 50% progress on airdrop
-        let mut airdrop_timer = AirDropTimer(Timer::from_seconds(10.0, false));
-        airdrop_timer.0.tick(airdrop_timer.0.duration() / 2);
-
-        app.add_startup_system(label_air_drop_indicator)
+        app.init_resource::<AirDropSchedule>()
+            .add_placeable(Item::Crate, "res://Airdrop.tscn", |entity| {
+                entity.insert(AirDrop::empty()).insert(Flammable);
+            })
             .add_startup_system(label_air_drop_progressbar)
+            .add_startup_system(start_airdrop_timer)
             .add_system(label_airdrops)
-            .add_system(collect_airdrops)
+            .add_system(bind_descent_nodes)
+            .add_system(open_crate.run_in_state(GameState::Playing))
+            .add_system(disassemble_crate.run_in_state(GameState::Playing))
+            .add_system(assemble_crate.run_in_state(GameState::Playing))
             .add_system(drop_airdrops)
-            .add_system(airdrop_indicator.as_visual_system())
-            .insert_resource(airdrop_timer)
+            .add_system(expire_airdrops.run_in_state(GameState::Playing))
+            .add_system(descend_airdrops.as_visual_system())
+            .add_system(
+                airdrop_indicator
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .insert_resource(AirDropTimer(Timer::from_seconds(0.0, false)))
+            .insert_resource(OpenedCrate(None))
+            .add_exit_system(GameState::Playing, close_crate)
             .add_exit_system(GameState::GameOver, on_restart);
     }
 }
 
+pub enum AirDropMode {
+    // A crate every interval, whether the last one was opened or not
+    FixedInterval,
+    // The next crate only comes after the last one was opened
+    OnCollect,
+    // Every crate makes the wait for the next one longer (in seconds)
+    EscalatingInterval { step: f32, max_interval: f32 },
+}
+
+// When and where the army drops crates. Insert it before adding the plugin to change the defaults.
+pub struct AirDropSchedule {
+    pub mode: AirDropMode,
+    // Seconds between drops (the first one comes after half of it)
+    pub interval: f32,
+    // How far from the player crates land
+    pub min_distance: f32,
+    pub max_distance: f32,
+    // No more crates are dropped while this many are lying around unopened
+    pub max_uncollected: usize,
+    // Seconds before an unopened crate is gone, if ever
+    pub expiry: Option<f32>,
+}
+
+impl Default for AirDropSchedule {
+    fn default() -> Self {
+        Self {
+            mode: AirDropMode::OnCollect,
+            interval: 10.0,
+            min_distance: 1200.0,
+            max_distance: 1800.0,
+            max_uncollected: 3,
+            expiry: Some(120.0),
+        }
+    }
+}
+
 #[derive(Component)]
-pub struct AirDrop(pub Vec<Part>);
+pub struct AirDrop {
+    pub parts: Vec<Part>,
+    pub bolts: u32,
+    // Whether the player has looked inside already
+    pub opened: bool,
+}
+
+impl AirDrop {
+    pub fn new(parts: Vec<Part>) -> Self {
+        Self {
+            parts,
+            bolts: CRATE_BOLTS,
+            opened: false,
+        }
+    }
+
+    // A crate put together by the player
+    pub fn empty() -> Self {
+        Self {
+            parts: vec![],
+            bolts: 0,
+            opened: true,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.parts.len() >= CRATE_CAPACITY
+    }
+
+    // What the player knows about the contents. Only crates they looked into were scouted.
+    pub fn contents_tooltip(&self) -> String {
+        if !self.opened {
+            "Unknown contents".to_string()
+        } else if self.parts.is_empty() {
+            "Empty".to_string()
+        } else {
+            self.parts
+                .iter()
+                .map(|part| format!("{:?}", part))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+// The crate the player is currently looking into
+pub struct OpenedCrate(pub Option<Entity>);
+
+pub fn crate_is_closed(opened_crate: Res<OpenedCrate>) -> bool {
+    opened_crate.0.is_none()
+}
 
 #[derive(Component)]
 pub struct BonusAirDrop;
 
+// A crate still hanging on its parachute. It can't be looted until it lands.
 #[derive(Component)]
-pub struct AirDropIndicator;
+pub struct Descending(Timer);
 
+// Entities of the parts of a descending crate that move or show up during the descent
 #[derive(Component)]
-pub struct AirDropIndicatorLabel;
+pub struct DescentNodes {
+    icon: Entity,
+    parachute: Entity,
+    shadow: Entity,
+    marker: Entity,
+    collision_shape: Entity,
+}
 
 #[derive(Component)]
 pub struct AirDropProgressBar;
 
 pub struct AirDropTimer(Timer);
 
-fn label_air_drop_indicator(mut commands: Commands, entities: Query<(&Name, Entity)>) {
-    let ent = entities
-        .iter()
-        .find_map(|(name, ent)| (name.as_str() == "AirdropIndicator").then_some(ent))
-        .unwrap();
-
-    commands.entity(ent).insert(AirDropIndicator);
+// Counts down until an unopened crate is gone
+#[derive(Component)]
+pub struct AirDropExpiry(Timer);
 
-    let ent = entities
-        .iter()
-        .find_map(|(name, ent)| (name.as_str() == "AirdropDistance").then_some(ent))
-        .unwrap();
+// Start with 50% progress on the first airdrop
+fn reset_airdrop_timer(airdrop_timer: &mut AirDropTimer, schedule: &AirDropSchedule) {
+    airdrop_timer.0 = Timer::from_seconds(schedule.interval, false);
+    airdrop_timer.0.tick(airdrop_timer.0.duration() / 2);
+}
 
-    commands.entity(ent).insert(AirDropIndicatorLabel);
+fn start_airdrop_timer(mut airdrop_timer: ResMut<AirDropTimer>, schedule: Res<AirDropSchedule>) {
+    reset_airdrop_timer(&mut airdrop_timer, &schedule);
 }
 
 fn label_air_drop_progressbar(mut commands: Commands, entities: Query<(&Name, Entity)>) {
@@ -79,7 +201,7 @@ fn label_airdrops(
 ) {
     for (groups, ent) in entities.iter() {
         if groups.is("airdrop") {
-            commands.entity(ent).insert(AirDrop(vec![
+            commands.entity(ent).insert(AirDrop::new(vec![
                 Part::random(),
                 Part::random(),
                 Part::random(),
@@ -92,7 +214,9 @@ fn drop_airdrops(
     mut commands: Commands,
     mut time: SystemDelta,
     mut airdrop_timer: ResMut<AirDropTimer>,
+    schedule: Res<AirDropSchedule>,
     player: Query<&Transform2D, With<Player>>,
+    airdrops: Query<&AirDrop, Without<BonusAirDrop>>,
     mut progress_bar: Query<&mut ErasedGodotRef, With<AirDropProgressBar>>,
     state: Res<CurrentState<GameState>>,
 ) {
@@ -102,170 +226,419 @@ fn drop_airdrops(
         return;
     }
 
-    airdrop_timer.0.tick(delta);
+    // the army waits until the player catches up with the crates already out there
+    let uncollected = airdrops.iter().filter(|air_drop| !air_drop.opened).count();
+    let waiting = uncollected >= schedule.max_uncollected;
+    if !waiting {
+        airdrop_timer.0.tick(delta);
+    }
 
     let mut progress_bar = progress_bar.single_mut();
     progress_bar
         .get::<ProgressBar>()
         .set_value(airdrop_timer.0.percent() as f64);
 
-    if airdrop_timer.0.just_finished() {
+    if !waiting && airdrop_timer.0.just_finished() {
         let mut airdrop_transform = *player.single();
 
+        let distance = schedule.min_distance
+            + rand::random::<f32>() * (schedule.max_distance - schedule.min_distance);
+
         airdrop_transform.set_rotation(rand::random::<f32>() * 2.0 * PI);
-        airdrop_transform.0 = airdrop_transform.translated(Vector2::UP * 1500.0);
+        airdrop_transform.0 = airdrop_transform.translated(Vector2::UP * distance);
         airdrop_transform.set_rotation(0.0);
 
-        info!("dropping airdrop at {:?}", airdrop_transform.origin);
+        drop_airdrop(
+            &mut commands,
+            airdrop_transform.origin,
+            AirDrop::new(vec![Part::random(), Part::random(), Part::random()]),
+        );
+
+        match schedule.mode {
+            AirDropMode::FixedInterval => airdrop_timer.0.reset(),
+            // the timer is reset once the crate is opened
+            AirDropMode::OnCollect => {}
+            AirDropMode::EscalatingInterval { step, max_interval } => {
+                let interval = (airdrop_timer.0.duration().as_secs_f32() + step).min(max_interval);
+                airdrop_timer.0 = Timer::from_seconds(interval, false);
+            }
+        }
+    }
+}
+
+// Unopened crates don't lie around forever
+fn expire_airdrops(
+    mut commands: Commands,
+    mut airdrops: Query<
+        (
+            &AirDrop,
+            Option<&mut AirDropExpiry>,
+            Option<&BonusAirDrop>,
+            &mut ErasedGodotRef,
+            Entity,
+        ),
+        Without<Descending>,
+    >,
+    schedule: Res<AirDropSchedule>,
+    mut airdrop_timer: ResMut<AirDropTimer>,
+    mut item_log: EventWriter<ItemLogEvent>,
+    mut time: SystemDelta,
+) {
+    let delta = time.delta();
+
+    let expiry = match schedule.expiry {
+        Some(expiry) => expiry,
+        None => return,
+    };
 
-        commands
-            .spawn()
-            .insert(GodotScene::from_path("res://Airdrop.tscn"))
-            .insert(airdrop_transform);
+    for (air_drop, expiry_timer, bonus, mut reference, ent) in airdrops.iter_mut() {
+        if air_drop.opened {
+            if expiry_timer.is_some() {
+                commands.entity(ent).remove::<AirDropExpiry>();
+            }
+            continue;
+        }
+
+        let mut expiry_timer = match expiry_timer {
+            Some(expiry_timer) => expiry_timer,
+            None => {
+                commands
+                    .entity(ent)
+                    .insert(AirDropExpiry(Timer::from_seconds(expiry, false)));
+                continue;
+            }
+        };
+
+        expiry_timer.0.tick(delta);
+
+        if expiry_timer.0.just_finished() {
+            reference.get::<Node>().queue_free();
+            item_log.send(ItemLogEvent(
+                "Someone else got to an airdrop first".to_string(),
+            ));
+
+            // nobody is going to open it, so don't wait for that
+            let on_collect = matches!(schedule.mode, AirDropMode::OnCollect);
+            if bonus.is_none() && on_collect && airdrop_timer.0.finished() {
+                airdrop_timer.0.reset();
+            }
+        }
     }
 }
 
-fn collect_airdrops(
+// Sends a crate down on a parachute. It will land at the given origin.
+pub fn drop_airdrop(commands: &mut Commands, origin: Vector2, air_drop: AirDrop) -> Entity {
+    info!("dropping airdrop at {:?}", origin);
+
+    commands
+        .spawn()
+        .insert(GodotScene::from_path("res://Airdrop.tscn"))
+        .insert(air_drop)
+        .insert(Flammable)
+        .insert(Descending(Timer::from_seconds(DESCENT_TIME, false)))
+        .insert(Transform2D(GodotTransform2D::IDENTITY.translated(origin)))
+        .id()
+}
+
+// Looks up the parts of a descending crate once, when its scene comes in
+fn bind_descent_nodes(
+    mut commands: Commands,
+    mut entities: Query<(Entity, &mut ErasedGodotRef, Option<&Descending>), Added<ErasedGodotRef>>,
+) {
+    let mut bindings = vec![];
+    for (ent, mut reference, descending) in entities.iter_mut() {
+        if descending.is_none() {
+            continue;
+        }
+
+        let node = reference.get::<Node>();
+        let instance_ids = [
+            "Icon",
+            "Icon/Parachute",
+            "Shadow",
+            "LandingMarker",
+            "CollisionShape2D",
+        ]
+        .map(|path| unsafe { node.get_node(path).unwrap().assume_safe().get_instance_id() });
+        bindings.push((ent, instance_ids));
+    }
+
+    for (air_drop, instance_ids) in bindings {
+        let [icon, parachute, shadow, marker, collision_shape] = instance_ids.map(|instance_id| {
+            entities
+                .iter()
+                .find_map(|(ent, reference, _)| {
+                    (reference.instance_id() == instance_id).then_some(ent)
+                })
+                .unwrap()
+        });
+
+        commands.entity(air_drop).insert(DescentNodes {
+            icon,
+            parachute,
+            shadow,
+            marker,
+            collision_shape,
+        });
+    }
+}
+
+fn descend_airdrops(
+    mut commands: Commands,
+    mut airdrops: Query<(&mut Descending, &DescentNodes, &Transform2D, Entity)>,
+    mut nodes: Query<&mut ErasedGodotRef, Without<Descending>>,
+    mut item_log: EventWriter<ItemLogEvent>,
+    mut noise: EventWriter<NoiseEvent>,
+    mut time: SystemDelta,
+) {
+    let delta = time.delta();
+
+    for (mut descending, descent_nodes, transform, ent) in airdrops.iter_mut() {
+        descending.0.tick(delta);
+        let landed = descending.0.finished();
+
+        // the crate gets bigger the closer it is to the camera
+        let height = 1.0 - descending.0.percent();
+        let scale = 0.25 * (1.0 + 0.5 * height);
+
+        let mut icon = nodes.get_mut(descent_nodes.icon).unwrap();
+        let icon = icon.get::<Node2D>();
+        icon.set_position(Vector2::UP * DESCENT_HEIGHT * height);
+        icon.set_scale(Vector2::new(scale, scale));
+
+        let mut parachute = nodes.get_mut(descent_nodes.parachute).unwrap();
+        parachute.get::<Node2D>().set_visible(!landed);
+
+        // and the shadow gets sharper the closer it is to the ground
+        let mut shadow = nodes.get_mut(descent_nodes.shadow).unwrap();
+        let shadow = shadow.get::<Node2D>();
+        shadow.set_visible(!landed);
+        shadow.set_modulate(Color::from_rgba(0.0, 0.0, 0.0, 0.35 * (1.0 - height)));
+
+        let mut marker = nodes.get_mut(descent_nodes.marker).unwrap();
+        marker.get::<Node2D>().set_visible(!landed);
+
+        let mut collision_shape = nodes.get_mut(descent_nodes.collision_shape).unwrap();
+        collision_shape
+            .get::<CollisionShape2D>()
+            .set_disabled(!landed);
+
+        if landed {
+            commands
+                .entity(ent)
+                .remove::<Descending>()
+                .remove::<DescentNodes>();
+
+            noise.send(NoiseEvent {
+                origin: transform.origin,
+                level: LANDING_NOISE,
+            });
+            item_log.send(ItemLogEvent("An airdrop has landed".to_string()));
+        }
+    }
+}
+
+fn open_crate(
     player_interact_volume: Query<&Collisions, With<PlayerInteractVolume>>,
-    mut player: Query<&mut Player>,
-    mut airdrops: Query<(&AirDrop, &mut ErasedGodotRef, Option<&BonusAirDrop>)>,
+    mut player: Query<(&mut Player, &Transform2D)>,
+    mut airdrops: Query<(&mut AirDrop, Option<&BonusAirDrop>), Without<Descending>>,
+    mut opened_crate: ResMut<OpenedCrate>,
     mut airdrop_timer: ResMut<AirDropTimer>,
+    schedule: Res<AirDropSchedule>,
     mut item_log: EventWriter<ItemLogEvent>,
+    mut noise: EventWriter<NoiseEvent>,
     mut score: ResMut<Score>,
 ) {
+    let input = Input::godot_singleton();
     let player_interact_volume = player_interact_volume.single();
 
-    for ent in player_interact_volume.recent_collisions() {
-        if let Ok((air_drop, mut reference, bonus)) = airdrops.get_mut(*ent) {
-            let reference = reference.get::<Node>();
-            reference.queue_free();
+    if let Some(crate_ent) = opened_crate.0 {
+        // Performing any action other than exchanging items closes the crate
+        let other_action = [
+            "open_crate",
+            "ui_cancel",
+            "set_goal",
+            "sprint",
+            "place_trap",
+            "reload",
+            "disassemble_crate",
+            "drop_item",
+            "pick_up",
+            "refill_turret",
+            "trigger_remote_alarms",
+        ]
+        .into_iter()
+        .any(|action| input.is_action_just_pressed(action, false));
+        let out_of_reach = !player_interact_volume.colliding().contains(&crate_ent)
+            || airdrops.get(crate_ent).is_err();
+
+        if other_action || out_of_reach {
+            debug!("Closing the crate.");
+            opened_crate.0 = None;
+        }
+
+        return;
+    }
 
-            let mut player = player.single_mut();
-            player.inventory.add_parts(&air_drop.0);
+    if !input.is_action_just_pressed("open_crate", false) {
+        return;
+    }
 
-            let bullets = 25;
+    let crate_ent = player_interact_volume
+        .colliding()
+        .iter()
+        .find(|ent| airdrops.get(**ent).is_ok());
 
-            for part in air_drop.0.iter() {
-                item_log.send(ItemLogEvent(format!("Picked up a {part:?}")));
-            }
-            item_log.send(ItemLogEvent(format!("Picked up {} bullets", bullets)));
+    if let Some(crate_ent) = crate_ent {
+        let (mut air_drop, bonus) = airdrops.get_mut(*crate_ent).unwrap();
+        let (mut player, player_transform) = player.single_mut();
 
-            if bonus.is_none() {
+        debug!("Opening the crate.");
+        opened_crate.0 = Some(*crate_ent);
+
+        noise.send(NoiseEvent {
+            origin: player_transform.origin,
+            level: OPEN_CRATE_NOISE,
+        });
+
+        if air_drop.bolts > 0 {
+            item_log.send(ItemLogEvent(format!("Picked up {} bolts", air_drop.bolts)));
+            player.ammo_count += air_drop.bolts;
+            air_drop.bolts = 0;
+        }
+
+        if !air_drop.opened {
+            air_drop.opened = true;
+
+            if bonus.is_none() && matches!(schedule.mode, AirDropMode::OnCollect) {
                 airdrop_timer.0.reset();
             }
 
             score.0 += 250;
-            player.ammo_count += bullets;
         }
     }
 }
 
+fn disassemble_crate(
+    mut commands: Commands,
+    player_interact_volume: Query<&Collisions, With<PlayerInteractVolume>>,
+    mut player: Query<&mut Player>,
+    mut airdrops: Query<(&AirDrop, &Transform2D, &mut ErasedGodotRef), Without<Descending>>,
+    mut opened_crate: ResMut<OpenedCrate>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
+
+    if !input.is_action_just_pressed("disassemble_crate", false) {
+        return;
+    }
+
+    let player_interact_volume = player_interact_volume.single();
+    let crate_ent = player_interact_volume
+        .colliding()
+        .iter()
+        .find(|ent| airdrops.get(**ent).is_ok())
+        .copied();
+
+    if let Some(crate_ent) = crate_ent {
+        let (air_drop, transform, mut reference) = airdrops.get_mut(crate_ent).unwrap();
+
+        if !air_drop.opened {
+            item_log.send(ItemLogEvent("Open the crate first".to_string()));
+            return;
+        }
+
+        // whatever was inside ends up in the sand
+        for part in air_drop.parts.iter() {
+            drop_part(&mut commands, *part, transform.origin);
+        }
+
+        reference.get::<Node>().queue_free();
+
+        if opened_crate.0 == Some(crate_ent) {
+            opened_crate.0 = None;
+        }
+
+        let mut player = player.single_mut();
+        player.inventory.add_parts(&[Part::Wood; CRATE_WOOD]);
+        item_log.send(ItemLogEvent(format!(
+            "Took the crate apart for {} wood",
+            CRATE_WOOD
+        )));
+    }
+}
+
+fn assemble_crate(mut player: Query<&mut Player>, mut item_log: EventWriter<ItemLogEvent>) {
+    let input = Input::godot_singleton();
+
+    if !input.is_action_just_pressed("assemble_crate", false) {
+        return;
+    }
+
+    let mut player = player.single_mut();
+    if player.inventory.can_craft(Item::Crate) {
+        player.inventory.craft(Item::Crate);
+        item_log.send(ItemLogEvent("Assembled a crate".to_string()));
+    } else {
+        item_log.send(ItemLogEvent("Not enough wood for a crate".to_string()));
+    }
+}
+
+fn close_crate(mut opened_crate: ResMut<OpenedCrate>) {
+    opened_crate.0 = None;
+}
+
+// Keep the off-screen indicator of every airdrop up to date. Only crates that changed need it.
 fn airdrop_indicator(
-    mut airdrop_indicator: Query<
-        (&mut Transform2D, &mut ErasedGodotRef),
-        (
-            With<AirDropIndicator>,
-            Without<AirDropIndicatorLabel>,
-            Without<AirDrop>,
-            Without<Player>,
-        ),
-    >,
-    mut airdrop_indicator_label: Query<
-        &mut ErasedGodotRef,
-        (
-            With<AirDropIndicatorLabel>,
-            Without<AirDrop>,
-            Without<AirDropIndicator>,
-        ),
-    >,
+    mut commands: Commands,
     mut airdrops: Query<
-        (&Transform2D, &mut ErasedGodotRef),
         (
-            With<AirDrop>,
-            Without<AirDropIndicator>,
-            Without<AirDropIndicatorLabel>,
+            &AirDrop,
+            Option<&BonusAirDrop>,
+            Option<&mut OffscreenTarget>,
+            Entity,
         ),
+        Or<(
+            Changed<AirDrop>,
+            Added<BonusAirDrop>,
+            Without<OffscreenTarget>,
+        )>,
     >,
-    player: Query<&Transform2D, With<Player>>,
+    crafting_assets: Res<CraftingAssets>,
 ) {
-    let (mut indicator_transform, mut indicator) = airdrop_indicator.single_mut();
-    let indicator = indicator.get::<Node2D>();
-
-    let player = player.single();
-
-    // get the closest airdrop
-    let airdrop = airdrops
-        .iter_mut()
-        .min_by(|(transform_a, _), (transform_b, _)| {
-            let dist_a = player.origin.distance_to(transform_a.origin);
-            let dist_b = player.origin.distance_to(transform_b.origin);
-            dist_a.partial_cmp(&dist_b).unwrap()
-        });
-
-    if let Some((air_drop_transform, mut air_drop)) = airdrop {
-        let mut airdrop_screen_origin = {
-            let air_drop = air_drop.get::<Node2D>();
-            air_drop.get_global_transform_with_canvas().origin
-        };
-
-        let mut indicator_label = airdrop_indicator_label.single_mut();
-        let indicator_label = indicator_label.get::<Label>();
-
-        let screen_size = Vector2::new(1280.0, 720.0);
-
-        // calculate the indicator's origin and keep the offset used
-        let indicator_origin_and_offset = if (airdrop_screen_origin.x <= 0.0
-            || airdrop_screen_origin.x >= screen_size.x)
-            || (airdrop_screen_origin.y <= 0.0 || airdrop_screen_origin.y >= screen_size.y)
-        {
-            let offset = 40.0;
-            let mut offset_vector2 = Vector2::ZERO;
-
-            if airdrop_screen_origin.x <= 0.0 {
-                airdrop_screen_origin.x = offset;
-                offset_vector2.x = offset;
-            } else if airdrop_screen_origin.x >= screen_size.x {
-                airdrop_screen_origin.x = screen_size.x - offset;
-                offset_vector2.x = -offset;
-            }
-
-            if airdrop_screen_origin.y <= 0.0 {
-                airdrop_screen_origin.y = offset;
-                offset_vector2.y = offset;
-            } else if airdrop_screen_origin.y >= screen_size.y {
-                airdrop_screen_origin.y = screen_size.y - offset;
-                offset_vector2.y = -offset;
-            }
-
-            Some((airdrop_screen_origin, offset_vector2))
+    for (air_drop, bonus, target, ent) in airdrops.iter_mut() {
+        // bonus airdrops stand out in gold
+        let color = if bonus.is_some() {
+            Color::from_rgba(0.95, 0.75, 0.2, 0.6)
         } else {
-            None
+            Color::from_rgba(0.39, 0.78, 0.39, 0.39)
         };
+        let tooltip = air_drop.contents_tooltip();
 
-        if let Some((origin, offset)) = indicator_origin_and_offset {
-            indicator_transform.0 = GodotTransform2D::IDENTITY.translated(origin);
-            indicator.set_visible(true);
-
-            let distance = air_drop_transform.origin.distance_to(player.origin);
-            indicator_label.set_position(offset, false);
-            indicator_label.set_text(format!("{:.0}m", distance / 8.0));
+        if let Some(mut target) = target {
+            target.color = color;
+            target.tooltip = tooltip;
         } else {
-            indicator.set_visible(false);
+            commands.entity(ent).insert(OffscreenTarget {
+                icon: Item::Crate.as_texture_handle(&crafting_assets).clone(),
+                color,
+                tooltip,
+            });
         }
-    } else {
-        indicator.set_visible(false);
     }
 }
 
 fn on_restart(
     mut airdrops: Query<&mut ErasedGodotRef, With<AirDrop>>,
     mut airdrop_timer: ResMut<AirDropTimer>,
+    schedule: Res<AirDropSchedule>,
+    mut opened_crate: ResMut<OpenedCrate>,
 ) {
     for mut airdrop in airdrops.iter_mut() {
         airdrop.get::<Node>().queue_free();
     }
 
-    airdrop_timer.0.reset();
-    let timer_duration = airdrop_timer.0.duration();
-    airdrop_timer.0.tick(timer_duration / 2);
+    opened_crate.0 = None;
+
+    reset_airdrop_timer(&mut airdrop_timer, &schedule);
 }
@@ -0,0 +1,109 @@
+use crate::{
+    map::MapView,
+    player::{Player, Target},
+};
+use bevy_godot::prelude::{
+    bevy_prelude::{With, Without},
+    godot_prelude::Vector2,
+    *,
+};
+
+pub struct CameraPlugin;
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraSettings::default())
+            .add_startup_system(label_camera)
+            .add_system(follow_aim.as_visual_system())
+            .add_system(zoom_camera.as_visual_system());
+    }
+}
+
+pub struct CameraSettings {
+    // How far (in units) the camera can move away from the player while aiming
+    pub max_offset: f32,
+    // How quickly the camera catches up. Higher is snappier.
+    pub easing: f32,
+    // Camera zoom while walking around and while looking at the map. Higher shows more.
+    pub zoom: f32,
+    pub map_zoom: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            max_offset: 300.0,
+            easing: 5.0,
+            zoom: 2.0,
+            map_zoom: 8.0,
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct PlayerCamera;
+
+fn label_camera(mut commands: Commands, entities: Query<(&Name, Entity)>) {
+    let camera = entities
+        .iter()
+        .find_map(|(name, ent)| (name.as_str() == "PlayerCamera").then_some(ent))
+        .unwrap();
+
+    commands.entity(camera).insert(PlayerCamera);
+}
+
+// While aiming the camera follows the midpoint between the player and the target, so it's
+// possible to observe a wider area around.
+fn follow_aim(
+    mut camera: Query<&mut ErasedGodotRef, (With<PlayerCamera>, Without<Target>)>,
+    mut target: Query<(&Transform2D, &mut ErasedGodotRef), (With<Target>, Without<PlayerCamera>)>,
+    player: Query<&Transform2D, With<Player>>,
+    settings: Res<CameraSettings>,
+    map: Res<MapView>,
+    mut time: SystemDelta,
+) {
+    let delta = time.delta_seconds();
+
+    let mut camera = camera.single_mut();
+    let camera = camera.get::<Camera2D>();
+    let (target_transform, mut target) = target.single_mut();
+    let player = player.single();
+
+    let desired_offset = if !map.0 && target.get::<Node2D>().is_visible() {
+        let offset = (target_transform.origin - player.origin) / 2.0;
+        if offset.length() > settings.max_offset {
+            offset.normalized() * settings.max_offset
+        } else {
+            offset
+        }
+    } else {
+        Vector2::ZERO
+    };
+
+    let current_offset = camera.offset();
+    let weight = 1.0 - (-settings.easing * delta).exp();
+    camera.set_offset(current_offset + (desired_offset - current_offset) * weight);
+}
+
+// Zoom out a lot while the map is open
+fn zoom_camera(
+    mut camera: Query<&mut ErasedGodotRef, With<PlayerCamera>>,
+    settings: Res<CameraSettings>,
+    map: Res<MapView>,
+    mut time: SystemDelta,
+) {
+    let delta = time.delta_seconds();
+
+    let mut camera = camera.single_mut();
+    let camera = camera.get::<Camera2D>();
+
+    let desired_zoom = if map.0 {
+        settings.map_zoom
+    } else {
+        settings.zoom
+    };
+
+    let current_zoom = camera.zoom().x;
+    let weight = 1.0 - (-settings.easing * delta).exp();
+    let zoom = current_zoom + (desired_zoom - current_zoom) * weight;
+    camera.set_zoom(Vector2::new(zoom, zoom));
+}
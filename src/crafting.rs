@@ -2,7 +2,7 @@ use bevy::log::*;
 use bevy_asset_loader::prelude::*;
 use bevy_godot::prelude::{bevy_prelude::Mut, *};
 use rand::prelude::SliceRandom;
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 #[derive(Debug, AssetCollection)]
 pub struct CraftingAssets {
@@ -10,6 +10,19 @@ pub struct CraftingAssets {
     proximity_bomb: Handle<GodotResource>,
     #[asset(path = "art/alarm_trap.tres")]
     alarm: Handle<GodotResource>,
+    #[asset(path = "art/crate.png")]
+    storage_crate: Handle<GodotResource>,
+    // TODO: Needs its own art
+    #[asset(path = "art/bomb.tres")]
+    fragmentation_bomb: Handle<GodotResource>,
+    // TODO: Needs its own art
+    #[asset(path = "art/bomb.tres")]
+    incendiary: Handle<GodotResource>,
+    #[asset(path = "art/sentry.svg")]
+    sentry_tower: Handle<GodotResource>,
+    // TODO: Needs its own art
+    #[asset(path = "art/sentry.svg")]
+    defense_turret: Handle<GodotResource>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component, PartialOrd, Ord)]
@@ -19,6 +32,9 @@ pub enum Part {
     Buzzer,
     Explosive,
     Motor,
+    Wood,
+    Mechanical,
+    Fuel,
 }
 
 impl Part {
@@ -27,28 +43,59 @@ impl Part {
         Self::Electronics,
         Self::Buzzer,
         Self::Explosive,
+        Self::Mechanical,
+        Self::Fuel,
         //Self::Motor,
+        // NOTE: Wood is intentionally missing. It only comes from taking crates apart.
     ];
 
     pub fn random() -> Self {
         let mut rng = rand::thread_rng();
         *Self::ALL.choose(&mut rng).unwrap()
     }
+
+    pub fn from_str(string: &str) -> Option<Self> {
+        Some(match string {
+            "Battery" => Self::Battery,
+            "Electronics" => Self::Electronics,
+            "Buzzer" => Self::Buzzer,
+            "Explosive" => Self::Explosive,
+            "Motor" => Self::Motor,
+            "Wood" => Self::Wood,
+            "Mechanical" => Self::Mechanical,
+            "Fuel" => Self::Fuel,
+            _ => return None,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component, PartialOrd, Ord)]
 pub enum Item {
     Alarm,
+    TimedAlarm,
+    RemoteAlarm,
     ProximityBomb,
+    FragmentationBomb,
+    Incendiary,
+    SentryTower,
+    DefenseTurret,
     Drone,
+    Crate,
 }
 
 impl Item {
     pub fn from_str(string: &str) -> Option<Self> {
         Some(match string {
             "Alarm" => Self::Alarm,
+            "TimedAlarm" => Self::TimedAlarm,
+            "RemoteAlarm" => Self::RemoteAlarm,
             "ProximityBomb" => Self::ProximityBomb,
+            "FragmentationBomb" => Self::FragmentationBomb,
+            "Incendiary" => Self::Incendiary,
+            "SentryTower" => Self::SentryTower,
+            "DefenseTurret" => Self::DefenseTurret,
             "Drone" => Self::Drone,
+            "Crate" => Self::Crate,
             _ => return None,
         })
     }
@@ -56,27 +103,31 @@ impl Item {
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Alarm => "Alarm",
+            Self::TimedAlarm => "Timed Alarm",
+            Self::RemoteAlarm => "Remote Alarm",
             Self::ProximityBomb => "Proximity Bomb",
+            Self::FragmentationBomb => "Fragmentation Bomb",
+            Self::Incendiary => "Incendiary Device",
+            Self::SentryTower => "Sentry Tower",
+            Self::DefenseTurret => "Defense Turret",
             Self::Drone => "Drone",
+            Self::Crate => "Crate",
         }
     }
 
     pub fn as_texture_handle<'a>(&self, assets: &'a CraftingAssets) -> &'a Handle<GodotResource> {
         match self {
             Self::ProximityBomb => &assets.proximity_bomb,
-            Self::Alarm => &assets.alarm,
+            Self::FragmentationBomb => &assets.fragmentation_bomb,
+            Self::Incendiary => &assets.incendiary,
+            Self::SentryTower => &assets.sentry_tower,
+            Self::DefenseTurret => &assets.defense_turret,
+            Self::Alarm | Self::TimedAlarm | Self::RemoteAlarm => &assets.alarm,
+            Self::Crate => &assets.storage_crate,
             Self::Drone => todo!("missing drone art"),
         }
     }
 
-    pub fn scene_path(&self) -> &'static str {
-        match self {
-            Self::ProximityBomb => "res://traps/ProximityBomb.tscn",
-            Self::Alarm => "res://traps/Alarm.tscn",
-            _ => panic!("do not have a scene path for {:?}", self),
-        }
-    }
-
     pub fn ingredients(&self) -> Vec<Part> {
         use Part::*;
 
@@ -84,20 +135,91 @@ impl Item {
             Self::Alarm => {
                 vec![Electronics, Battery, Buzzer]
             }
+            // An alarm with a timer circuit
+            Self::TimedAlarm => {
+                let mut ingredients = Self::Alarm.ingredients();
+                ingredients.push(Electronics);
+                ingredients
+            }
+            // An alarm with a radio receiver, which needs its own battery
+            Self::RemoteAlarm => {
+                let mut ingredients = Self::Alarm.ingredients();
+                ingredients.extend([Electronics, Battery]);
+                ingredients
+            }
             Self::ProximityBomb => {
                 vec![Electronics, Battery, Explosive]
             }
+            // A proximity bomb wrapped in scrap metal
+            Self::FragmentationBomb => {
+                let mut ingredients = Self::ProximityBomb.ingredients();
+                ingredients.push(Mechanical);
+                ingredients
+            }
+            Self::Incendiary => {
+                vec![Fuel, Explosive]
+            }
+            Self::SentryTower => {
+                vec![Wood, Electronics, Battery]
+            }
+            // A sentry tower with a crossbow on top
+            Self::DefenseTurret => {
+                let mut ingredients = Self::SentryTower.ingredients();
+                ingredients.extend([Wood, Mechanical]);
+                ingredients
+            }
             Self::Drone => {
                 vec![Electronics, Battery, Motor]
             }
+            Self::Crate => {
+                vec![Wood, Wood]
+            }
+        }
+    }
+
+    pub fn alarm_timing(&self) -> Option<AlarmTiming> {
+        match self {
+            Self::Alarm => Some(AlarmTiming {
+                start_delay: Some(0.0),
+                battery_life: 60.0,
+                ring_period: 15.0,
+                quiet_period: 5.0,
+            }),
+            // Gives the player time to get away, then rings until the battery dies
+            Self::TimedAlarm => Some(AlarmTiming {
+                start_delay: Some(10.0),
+                battery_life: 60.0,
+                ring_period: 60.0,
+                quiet_period: 5.0,
+            }),
+            Self::RemoteAlarm => Some(AlarmTiming {
+                start_delay: None,
+                battery_life: 45.0,
+                ring_period: 15.0,
+                quiet_period: 5.0,
+            }),
+            _ => None,
         }
     }
 }
 
+// How an alarm device behaves once placed (in seconds)
+#[derive(Debug, Clone, Copy)]
+pub struct AlarmTiming {
+    // Time until it starts ringing. `None` waits for the player to set it off remotely.
+    pub start_delay: Option<f32>,
+    // How long the battery lasts once the alarm started
+    pub battery_life: f32,
+    pub ring_period: f32,
+    pub quiet_period: f32,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Inventory {
     parts: HashMap<Part, u32>,
     items: HashMap<Item, u32>,
+    // What's left of the batteries of devices that were placed before and picked up again
+    used_batteries: HashMap<Item, Vec<Duration>>,
 }
 
 impl Inventory {
@@ -145,10 +267,50 @@ impl Inventory {
         }
     }
 
+    pub fn remove_part(&mut self, part: Part) -> bool {
+        match self.parts.get_mut(&part) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => {
+                warn!("tried to remove part: {:?} but did not have any", part);
+                false
+            }
+        }
+    }
+
     pub fn get_items(&self) -> &HashMap<Item, u32> {
         &self.items
     }
 
+    pub fn add_item(&mut self, item: Item) {
+        info!("giving player item: {:?}", item);
+        *self.items.entry(item).or_default() += 1;
+    }
+
+    // Puts a placed device back. Its battery, if it has one, goes with it.
+    pub fn add_used_item(&mut self, item: Item, battery: Option<Duration>) {
+        self.add_item(item);
+        if let Some(battery) = battery {
+            self.used_batteries.entry(item).or_default().push(battery);
+        }
+    }
+
+    // Used devices are placed first, so no fresh battery goes to waste
+    pub fn take_used_battery(&mut self, item: Item) -> Option<Duration> {
+        self.used_batteries.get_mut(&item).and_then(Vec::pop)
+    }
+
+    // The item shown in the given slot of the item bar
+    pub fn item_in_slot(&self, slot: u16) -> Option<Item> {
+        self.items
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .nth(slot as usize)
+            .map(|(item, _)| *item)
+    }
+
     pub fn use_item(&mut self, item: &Item) {
         if let Some(count) = self.items.get_mut(item) {
             *count -= 1;
@@ -0,0 +1,201 @@
+use crate::{
+    crafting::{CraftingAssets, Item, Part},
+    placement::is_not_placing,
+    player::{Player, PlayerInteractVolume},
+    traps::{fire::Flammable, UsedBattery},
+    ui::{offscreen_indicator::is_on_screen, text_log::ItemLogEvent},
+    GameState, SelectedItemSlot,
+};
+use bevy::log::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, EventWriter, With, Without},
+    godot_prelude::Vector2,
+    *,
+};
+use iyes_loopless::prelude::*;
+use rand::prelude::*;
+use std::f32::consts::PI;
+
+// Items further away than this are lost in the sand as soon as they are out of sight
+const LOST_DISTANCE: f32 = 1000.0;
+
+pub struct GroundItemsPlugin;
+impl Plugin for GroundItemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(pick_up_ground_item.run_in_state(GameState::Playing))
+            // the item about to be placed stays in hand until it's placed or called off
+            .add_system(
+                drop_selected_item
+                    .run_in_state(GameState::Playing)
+                    .run_if(is_not_placing),
+            )
+            .add_system(
+                lose_ground_items
+                    .as_visual_system()
+                    .run_in_state(GameState::Playing),
+            )
+            .add_system(
+                show_ground_item_icons
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .add_exit_system(GameState::GameOver, on_restart);
+    }
+}
+
+// Something lying in the sand
+#[derive(Debug, Component)]
+pub enum GroundItem {
+    Part(Part),
+    Item(Item),
+}
+
+// Drops a part on the ground somewhere around the origin
+pub fn drop_part(commands: &mut Commands, part: Part, origin: Vector2) {
+    let mut rng = thread_rng();
+    let displacement =
+        Vector2::UP.rotated(rng.gen_range(0.0..(2.0 * PI))) * rng.gen_range(80.0..120.0);
+
+    spawn_ground_item(commands, GroundItem::Part(part), origin + displacement);
+}
+
+fn spawn_ground_item(commands: &mut Commands, item: GroundItem, origin: Vector2) -> Entity {
+    let flammable = matches!(
+        item,
+        GroundItem::Part(Part::Wood | Part::Fuel)
+            | GroundItem::Item(Item::Crate | Item::Incendiary)
+    );
+
+    let mut ground_item = commands.spawn();
+    ground_item
+        .insert(GodotScene::from_path("res://GroundItem.tscn"))
+        .insert(item)
+        .insert(Transform2D(GodotTransform2D::IDENTITY.translated(origin)));
+
+    if flammable {
+        ground_item.insert(Flammable);
+    }
+
+    ground_item.id()
+}
+
+// Dropped items look like what they are. Parts are all just packages.
+fn show_ground_item_icons(
+    mut items: Query<(&GroundItem, &mut ErasedGodotRef), Added<ErasedGodotRef>>,
+    crafting_assets: Res<CraftingAssets>,
+    assets: Res<Assets<GodotResource>>,
+) {
+    for (item, mut reference) in items.iter_mut() {
+        if let GroundItem::Item(item) = item {
+            let icon = reference.get::<Node>().get_node("Icon").unwrap();
+            let icon = unsafe { icon.assume_safe() }.cast::<Sprite>().unwrap();
+            let texture = assets
+                .get(item.as_texture_handle(&crafting_assets))
+                .unwrap();
+            icon.set_texture(texture.0.clone().cast::<Texture>().unwrap());
+        }
+    }
+}
+
+fn drop_selected_item(
+    mut commands: Commands,
+    mut player: Query<(&mut Player, &Transform2D)>,
+    selected_slot: Res<SelectedItemSlot>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
+
+    if !input.is_action_just_pressed("drop_item", false) {
+        return;
+    }
+
+    let (mut player, player_transform) = player.single_mut();
+
+    let item = match selected_slot
+        .0
+        .and_then(|slot| player.inventory.item_in_slot(slot))
+    {
+        Some(item) => item,
+        None => return,
+    };
+
+    let battery = player.inventory.take_used_battery(item);
+    player.inventory.use_item(&item);
+
+    // put it down in front of the player
+    let origin = player_transform.origin + player_transform.basis_xform_inv(Vector2::UP) * 60.0;
+    let ground_item = spawn_ground_item(&mut commands, GroundItem::Item(item), origin);
+
+    // a used device keeps what's left of its battery while lying around
+    if let Some(battery) = battery {
+        commands.entity(ground_item).insert(UsedBattery(battery));
+    }
+
+    item_log.send(ItemLogEvent(format!("Dropped a {}", item.as_str())));
+}
+
+fn pick_up_ground_item(
+    player_interact_volume: Query<&Collisions, With<PlayerInteractVolume>>,
+    mut player: Query<&mut Player>,
+    mut items: Query<(&GroundItem, Option<&UsedBattery>, &mut ErasedGodotRef)>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
+
+    if !input.is_action_just_pressed("pick_up", false) {
+        return;
+    }
+
+    let player_interact_volume = player_interact_volume.single();
+    let ent = player_interact_volume
+        .colliding()
+        .iter()
+        .find(|ent| items.get(**ent).is_ok());
+
+    if let Some(ent) = ent {
+        let (item, battery, mut reference) = items.get_mut(*ent).unwrap();
+        reference.get::<Node>().queue_free();
+
+        let mut player = player.single_mut();
+        match item {
+            GroundItem::Part(part) => {
+                player.inventory.add_part(*part);
+                item_log.send(ItemLogEvent(format!("Picked up a {:?}", part)));
+            }
+            GroundItem::Item(item) => {
+                player
+                    .inventory
+                    .add_used_item(*item, battery.map(|UsedBattery(remaining)| *remaining));
+                item_log.send(ItemLogEvent(format!("Picked up a {}", item.as_str())));
+            }
+        }
+    }
+}
+
+// Whatever is left far behind and out of sight is lost in the sand forever
+fn lose_ground_items(
+    mut items: Query<(&GroundItem, &Transform2D, &mut ErasedGodotRef), Without<Player>>,
+    player: Query<&Transform2D, With<Player>>,
+) {
+    let player = player.single();
+
+    for (item, transform, mut reference) in items.iter_mut() {
+        if transform.origin.distance_to(player.origin) < LOST_DISTANCE {
+            continue;
+        }
+
+        let node = reference.get::<Node2D>();
+        let screen_origin = node.get_global_transform_with_canvas().origin;
+
+        if !is_on_screen(node.get_viewport_rect(), screen_origin) {
+            debug!("{:?} got lost in the sand", item);
+            node.queue_free();
+        }
+    }
+}
+
+fn on_restart(mut items: Query<&mut ErasedGodotRef, With<GroundItem>>) {
+    for mut item in items.iter_mut() {
+        item.get::<Node>().queue_free();
+    }
+}
@@ -6,10 +6,16 @@ use iyes_loopless::prelude::*;
 use std::time::Instant;
 
 pub mod airdrops;
+mod camera;
 mod crafting;
+mod ground_items;
+mod map;
+mod placement;
 mod player;
+mod shelters;
 mod traps;
 mod ui;
+mod waypoints;
 mod zombies;
 
 fn init(_handle: &InitHandle) {}
@@ -20,15 +26,28 @@ fn build_app(app: &mut App) {
             LoadingState::new(GameState::Loading)
                 .continue_to_state(GameState::Playing)
                 .with_collection::<zombies::ZombieAssets>()
-                .with_collection::<crafting::CraftingAssets>(),
+                .with_collection::<player::PlayerAssets>()
+                .with_collection::<ui::offscreen_indicator::OffscreenIndicatorAssets>()
+                .with_collection::<crafting::CraftingAssets>()
+                .with_collection::<map::MapAssets>()
+                .with_collection::<ui::minimap::MinimapAssets>()
+                .with_collection::<ui::compass::CompassAssets>()
+                .with_collection::<traps::fire::FireAssets>()
+                .with_collection::<shelters::ShelterAssets>(),
         )
         .insert_resource(Score(0))
         .insert_resource(SelectedItemSlot(Some(0)))
         .add_exit_system(GameState::Loading, set_round_start)
         .add_exit_system(GameState::GameOver, set_round_start)
         .add_plugin(player::PlayerPlugin)
+        .add_plugin(camera::CameraPlugin)
+        .add_plugin(map::MapPlugin)
+        .add_plugin(placement::PlacementPlugin)
+        .add_plugin(waypoints::WaypointsPlugin)
         .add_plugin(zombies::ZombiesPlugin)
         .add_plugin(airdrops::AirDropsPlugin)
+        .add_plugin(shelters::SheltersPlugin)
+        .add_plugin(ground_items::GroundItemsPlugin)
         .add_plugin(ui::UiPlugin)
         .add_plugin(traps::TrapsPlugin);
 }
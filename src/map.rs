@@ -0,0 +1,290 @@
+use crate::{
+    airdrops::{AirDrop, BonusAirDrop},
+    crafting::{CraftingAssets, Item},
+    player::{Activity, Player},
+    waypoints::Waypoint,
+    GameState,
+};
+use bevy::log::*;
+use bevy_asset_loader::prelude::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, Or, With, Without},
+    godot_prelude::Color,
+    *,
+};
+use iyes_loopless::prelude::*;
+
+pub struct MapPlugin;
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(label_marker_container)
+            .add_system(label_markers)
+            .add_system(toggle_map.run_in_state(GameState::Playing))
+            .add_system(
+                mark_map_targets
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .add_system(
+                spawn_markers
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading)
+                    .run_if(map_is_open),
+            )
+            .add_system(
+                update_markers
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .insert_resource(MapView(false))
+            .insert_resource(MarkerPool(0))
+            .add_exit_system(GameState::Playing, close_map);
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct MapAssets {
+    #[asset(path = "MapMarker.tscn")]
+    marker_scene: Handle<GodotResource>,
+    #[asset(path = "art/Arrow.png")]
+    player_icon: Handle<GodotResource>,
+    #[asset(path = "art/x-circle.svg")]
+    waypoint_icon: Handle<GodotResource>,
+}
+
+// Whether the player is looking at the map. The desert doesn't stop while they do.
+pub struct MapView(pub bool);
+
+pub fn map_is_open(map: Res<MapView>) -> bool {
+    map.0
+}
+
+pub fn map_is_closed(map: Res<MapView>) -> bool {
+    !map.0
+}
+
+// Anything with this component is marked on the map
+#[derive(Component)]
+pub struct MapTarget {
+    pub icon: Handle<GodotResource>,
+    pub color: Color,
+    pub tooltip: String,
+}
+
+// Marks a target on the map. Markers are pooled and reassigned as targets come and go.
+#[derive(Component)]
+pub struct MapMarker(Option<Entity>);
+
+// The node holding all the markers
+#[derive(Component)]
+struct MarkerContainer;
+
+// How many markers were instanced so far
+struct MarkerPool(usize);
+
+fn label_marker_container(mut commands: Commands, entities: Query<(&Name, Entity)>) {
+    let ent = entities
+        .iter()
+        .find_map(|(name, ent)| (name.as_str() == "MapMarkers").then_some(ent))
+        .unwrap();
+
+    commands.entity(ent).insert(MarkerContainer);
+}
+
+fn label_markers(
+    mut commands: Commands,
+    entities: Query<(&Groups, Entity), (Added<ErasedGodotRef>, Without<MapMarker>)>,
+) {
+    for (groups, ent) in entities.iter() {
+        if groups.is("map_marker") {
+            commands.entity(ent).insert(MapMarker(None));
+        }
+    }
+}
+
+fn toggle_map(mut map: ResMut<MapView>, mut player: Query<&mut Activity, With<Player>>) {
+    let input = Input::godot_singleton();
+
+    let toggle = input.is_action_just_pressed("toggle_map", false);
+    let cancel = map.0 && input.is_action_just_pressed("ui_cancel", false);
+    if !toggle && !cancel {
+        return;
+    }
+
+    map.0 = !map.0;
+
+    // looking at the map the player keeps their head down
+    let mut activity = player.single_mut();
+    *activity = if map.0 {
+        Activity::Ducking
+    } else {
+        Activity::Standing
+    };
+
+    debug!("Map open: {}. Now {activity:?}", map.0);
+}
+
+fn close_map(mut map: ResMut<MapView>, mut player: Query<&mut Activity, With<Player>>) {
+    if map.0 {
+        map.0 = false;
+        *player.single_mut() = Activity::Standing;
+    }
+}
+
+// Keep the map targets in sync with what's going on in the desert
+fn mark_map_targets(
+    mut commands: Commands,
+    mut targets: Query<
+        (
+            Option<&AirDrop>,
+            Option<&BonusAirDrop>,
+            Option<&Player>,
+            Option<&Waypoint>,
+            Option<&mut MapTarget>,
+            Entity,
+        ),
+        Or<(With<AirDrop>, With<Player>, With<Waypoint>)>,
+    >,
+    crafting_assets: Res<CraftingAssets>,
+    map_assets: Res<MapAssets>,
+) {
+    for (air_drop, bonus, player, waypoint, map_target, ent) in targets.iter_mut() {
+        let (icon, color, tooltip) = if let Some(air_drop) = air_drop {
+            let color = if bonus.is_some() {
+                Color::from_rgba(0.95, 0.75, 0.2, 1.0)
+            } else {
+                Color::from_rgba(0.39, 0.78, 0.39, 1.0)
+            };
+            (Item::Crate, color, air_drop.contents_tooltip())
+        } else if player.is_some() || waypoint.is_some() {
+            if map_target.is_none() {
+                let map_target = if player.is_some() {
+                    MapTarget {
+                        icon: map_assets.player_icon.clone(),
+                        color: Color::from_rgba(1.0, 1.0, 1.0, 1.0),
+                        tooltip: "You".to_string(),
+                    }
+                } else {
+                    MapTarget {
+                        icon: map_assets.waypoint_icon.clone(),
+                        color: Color::from_rgba(0.3, 0.8, 1.0, 1.0),
+                        tooltip: "Waypoint".to_string(),
+                    }
+                };
+                commands.entity(ent).insert(map_target);
+            }
+            continue;
+        } else {
+            continue;
+        };
+
+        if let Some(mut map_target) = map_target {
+            map_target.color = color;
+            map_target.tooltip = tooltip;
+        } else {
+            commands.entity(ent).insert(MapTarget {
+                icon: icon.as_texture_handle(&crafting_assets).clone(),
+                color,
+                tooltip,
+            });
+        }
+    }
+}
+
+// Grow the marker pool, so there is one marker per target
+fn spawn_markers(
+    mut container: Query<&mut ErasedGodotRef, With<MarkerContainer>>,
+    targets: Query<(), With<MapTarget>>,
+    mut pool: ResMut<MarkerPool>,
+    map_assets: Res<MapAssets>,
+    assets: Res<Assets<GodotResource>>,
+) {
+    let missing = targets.iter().count().saturating_sub(pool.0);
+    if missing == 0 {
+        return;
+    }
+
+    let mut container = container.single_mut();
+    let container = container.get::<Node>();
+
+    let scene = assets
+        .get(&map_assets.marker_scene)
+        .unwrap()
+        .0
+        .clone()
+        .cast::<PackedScene>()
+        .unwrap();
+    let scene = unsafe { scene.assume_safe() };
+
+    for _ in 0..missing {
+        let marker = scene
+            .instance(PackedScene::GEN_EDIT_STATE_DISABLED)
+            .unwrap();
+        container.add_child(marker, false);
+    }
+
+    debug!("Instanced {missing} map markers");
+    pool.0 += missing;
+}
+
+fn update_markers(
+    mut container: Query<&mut ErasedGodotRef, (With<MarkerContainer>, Without<MapMarker>)>,
+    mut markers: Query<
+        (&mut MapMarker, &mut Transform2D, &mut ErasedGodotRef),
+        (Without<MapTarget>, Without<MarkerContainer>),
+    >,
+    targets: Query<(&MapTarget, &Transform2D, Entity), Without<MapMarker>>,
+    mut player: Query<
+        &mut ErasedGodotRef,
+        (With<Player>, Without<MapMarker>, Without<MarkerContainer>),
+    >,
+    map: Res<MapView>,
+    assets: Res<Assets<GodotResource>>,
+) {
+    let mut container = container.single_mut();
+    let container = container.get::<Node2D>();
+
+    container.set_visible(map.0);
+    if !map.0 {
+        return;
+    }
+
+    // where the camera currently puts things on the screen
+    let canvas_transform = player.single_mut().get::<Node2D>().get_canvas_transform();
+
+    // hand out free markers to the targets that don't have one yet
+    let mut unassigned = targets
+        .iter()
+        .map(|(_, _, ent)| ent)
+        .filter(|ent| !markers.iter().any(|(marker, _, _)| marker.0 == Some(*ent)))
+        .collect::<Vec<_>>();
+
+    for (mut marker, _, _) in markers.iter_mut() {
+        if marker.0.map_or(true, |ent| targets.get(ent).is_err()) {
+            marker.0 = unassigned.pop();
+        }
+    }
+
+    for (marker, mut marker_transform, mut marker_reference) in markers.iter_mut() {
+        let marker_node = marker_reference.get::<Node2D>();
+
+        let (target, target_transform, _) = match marker.0.and_then(|ent| targets.get(ent).ok()) {
+            Some(target) => target,
+            None => {
+                marker_node.set_visible(false);
+                continue;
+            }
+        };
+
+        marker_transform.0 =
+            GodotTransform2D::IDENTITY.translated(canvas_transform.xform(target_transform.origin));
+        marker_node.set_visible(true);
+
+        let icon = marker_node.get_node("Icon").unwrap();
+        let icon = unsafe { icon.assume_safe() }.cast::<TextureRect>().unwrap();
+        let texture = assets.get(&target.icon).unwrap();
+        icon.set_texture(texture.0.clone().cast::<Texture>().unwrap());
+        icon.set_modulate(target.color);
+        icon.set_tooltip(target.tooltip.as_str());
+    }
+}
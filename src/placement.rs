@@ -0,0 +1,264 @@
+use crate::{
+    airdrops::AirDrop,
+    crafting::{CraftingAssets, Item},
+    map::map_is_closed,
+    player::{mouse_position, Player, Target},
+    shelters::Shelter,
+    traps::{PlacedTrap, UsedBattery},
+    ui::text_log::ItemLogEvent,
+    GameState, SelectedItemSlot,
+};
+use bevy::{ecs::system::EntityCommands, log::*};
+use bevy_godot::prelude::{
+    bevy_prelude::{EventWriter, Or, With, Without},
+    godot_prelude::Color,
+    *,
+};
+use iyes_loopless::prelude::*;
+use std::{collections::HashMap, f32::consts::PI};
+
+// How far from the player things can be placed
+const PLACEMENT_REACH: f32 = 150.0;
+// How close to other traps, crates and shelter hatches something can be placed
+const MIN_SPACING: f32 = 60.0;
+// How much one press of a rotate key turns the device
+const ROTATION_STEP: f32 = PI / 8.0;
+
+pub struct PlacementPlugin;
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlacementMode>()
+            .init_resource::<PlaceableRegistry>()
+            .add_system(
+                place_item
+                    .as_physics_system()
+                    .run_in_state(GameState::Playing)
+                    .run_if(map_is_closed),
+            )
+            .add_exit_system(GameState::Playing, cancel_placement);
+    }
+}
+
+// The item the player is about to place, if any. Its ghost follows the mouse around.
+#[derive(Default)]
+pub struct PlacementMode(Option<Placement>);
+
+struct Placement {
+    item: Item,
+    rotation: f32,
+}
+
+pub fn is_not_placing(mode: Res<PlacementMode>) -> bool {
+    mode.0.is_none()
+}
+
+// What it takes to put an item down in the desert
+#[derive(Clone, Copy)]
+pub struct Placeable {
+    pub scene: &'static str,
+    // Adds what the placed entity needs on top of what its scene gets labelled with
+    pub setup: fn(&mut EntityCommands),
+}
+
+// Everything the player can place, by the item they carry around
+#[derive(Default)]
+pub struct PlaceableRegistry(HashMap<Item, Placeable>);
+
+impl PlaceableRegistry {
+    pub fn get(&self, item: Item) -> Option<Placeable> {
+        self.0.get(&item).copied()
+    }
+}
+
+pub trait AddPlaceable {
+    fn add_placeable(
+        &mut self,
+        item: Item,
+        scene: &'static str,
+        setup: fn(&mut EntityCommands),
+    ) -> &mut Self;
+}
+
+impl AddPlaceable for App {
+    fn add_placeable(
+        &mut self,
+        item: Item,
+        scene: &'static str,
+        setup: fn(&mut EntityCommands),
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(PlaceableRegistry::default)
+            .0
+            .insert(item, Placeable { scene, setup });
+        self
+    }
+}
+
+// The aiming marker doubles as the ghost of the item being placed
+fn show_ghost(target: &mut ErasedGodotRef, texture: Option<Ref<Texture>>) {
+    let target = target.get::<Node2D>();
+    let ghost = target.get_node("Ghost").unwrap();
+    let ghost = unsafe { ghost.assume_safe() }.cast::<Sprite>().unwrap();
+
+    match texture {
+        Some(texture) => {
+            ghost.set_texture(texture);
+            ghost.set_rotation(0.0);
+            ghost.set_visible(true);
+            target.set_visible(true);
+        }
+        None => {
+            ghost.set_visible(false);
+            target.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, 1.0));
+            target.set_visible(false);
+        }
+    }
+}
+
+fn place_item(
+    mut commands: Commands,
+    mut mode: ResMut<PlacementMode>,
+    mut player: Query<(&mut Player, &Transform2D, &mut ErasedGodotRef), Without<Target>>,
+    mut target: Query<(&mut ErasedGodotRef, &mut Transform2D), (With<Target>, Without<Player>)>,
+    obstacles: Query<
+        &Transform2D,
+        (
+            Or<(With<PlacedTrap>, With<AirDrop>)>,
+            Without<Player>,
+            Without<Target>,
+        ),
+    >,
+    shelters: Query<&Shelter>,
+    selected_slot: Res<SelectedItemSlot>,
+    registry: Res<PlaceableRegistry>,
+    crafting_assets: Res<CraftingAssets>,
+    assets: Res<Assets<GodotResource>>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
+    let (mut player, player_transform, mut player_reference) = player.single_mut();
+    let (mut target, mut target_transform) = target.single_mut();
+
+    let selected = selected_slot
+        .0
+        .and_then(|slot| player.inventory.item_in_slot(slot));
+
+    let placement = match mode.0.as_mut() {
+        Some(placement) => placement,
+        None => {
+            if !input.is_action_just_pressed("place_trap", false) {
+                return;
+            }
+
+            match selected {
+                Some(item) if registry.get(item).is_some() => {
+                    debug!("Placing {:?}", item);
+                    let texture = assets
+                        .get(item.as_texture_handle(&crafting_assets))
+                        .unwrap()
+                        .0
+                        .clone()
+                        .cast::<Texture>()
+                        .unwrap();
+                    show_ghost(&mut target, Some(texture));
+                    mode.0 = Some(Placement {
+                        item,
+                        rotation: 0.0,
+                    });
+                }
+                Some(item) => warn!("{:?} can't be placed", item),
+                None => {}
+            }
+            return;
+        }
+    };
+
+    // switching to another item or pressing escape calls it off
+    if input.is_action_just_pressed("ui_cancel", false) || selected != Some(placement.item) {
+        debug!("Not placing {:?} after all", placement.item);
+        mode.0 = None;
+        show_ghost(&mut target, None);
+        return;
+    }
+
+    if input.is_action_just_pressed("rotate_placement_left", false) {
+        placement.rotation -= ROTATION_STEP;
+    }
+    if input.is_action_just_pressed("rotate_placement_right", false) {
+        placement.rotation += ROTATION_STEP;
+    }
+
+    let mouse_position = mouse_position(player_reference.get::<Node2D>());
+    let offset = mouse_position - player_transform.origin;
+    let origin = if offset.length() > PLACEMENT_REACH {
+        player_transform.origin + offset.normalized() * PLACEMENT_REACH
+    } else {
+        mouse_position
+    };
+
+    // crates are solid, the player would get stuck in one dropped on top of them
+    let fits = obstacles
+        .iter()
+        .all(|obstacle| obstacle.origin.distance_to(origin) >= MIN_SPACING)
+        && shelters
+            .iter()
+            .all(|shelter| shelter.hatch().distance_to(origin) >= MIN_SPACING)
+        && (placement.item != Item::Crate
+            || player_transform.origin.distance_to(origin) >= MIN_SPACING);
+
+    target_transform.origin = origin;
+    let target_node = target.get::<Node2D>();
+    target_node.set_modulate(if fits {
+        Color::from_rgba(0.4, 0.9, 0.4, 0.8)
+    } else {
+        Color::from_rgba(0.9, 0.3, 0.3, 0.8)
+    });
+    let ghost = target_node.get_node("Ghost").unwrap();
+    unsafe { ghost.assume_safe() }
+        .cast::<Node2D>()
+        .unwrap()
+        .set_rotation(placement.rotation as f64);
+
+    if !input.is_action_just_pressed("place_trap", false) {
+        return;
+    }
+
+    if !fits {
+        item_log.send(ItemLogEvent(format!(
+            "There's no room for the {} there",
+            placement.item.as_str()
+        )));
+        return;
+    }
+
+    let item = placement.item;
+    let mut transform = Transform2D(GodotTransform2D::IDENTITY.translated(origin));
+    transform.set_rotation(placement.rotation);
+
+    mode.0 = None;
+    show_ghost(&mut target, None);
+
+    if let Some(placeable) = registry.get(item) {
+        let battery = player.inventory.take_used_battery(item);
+        player.inventory.use_item(&item);
+
+        let mut placed = commands.spawn();
+        placed
+            .insert(GodotScene::from_path(placeable.scene))
+            .insert(transform);
+        (placeable.setup)(&mut placed);
+
+        if let Some(battery) = battery {
+            placed.insert(UsedBattery(battery));
+        }
+    }
+}
+
+fn cancel_placement(
+    mut mode: ResMut<PlacementMode>,
+    mut target: Query<&mut ErasedGodotRef, With<Target>>,
+) {
+    if mode.0.take().is_some() {
+        show_ghost(&mut target.single_mut(), None);
+    }
+}
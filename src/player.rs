@@ -1,28 +1,59 @@
 use crate::{
+    airdrops::crate_is_closed,
     crafting::{Inventory, Item},
-    zombies::Zombie,
-    GameState, Hp, SelectedItemSlot,
+    map::map_is_closed,
+    placement::is_not_placing,
+    ui::text_log::ItemLogEvent,
+    zombies::{NoiseEvent, Zombie},
+    GameState, Hp,
 };
 use bevy::log::*;
+use bevy_asset_loader::prelude::*;
 use bevy_godot::prelude::{
-    bevy_prelude::{Added, With, Without},
+    bevy_prelude::{Added, EventWriter, Local, With, Without},
     godot_prelude::Vector2,
     *,
 };
 use iyes_loopless::prelude::*;
-use std::f64::consts::PI;
+use std::{
+    f64::consts::PI,
+    time::{Duration, Instant},
+};
 
 // TODO: Is there a way to set those in Godot and read them here? It would be nice to be able to experiment with constants on the fly.
 const WALKING_SPEED: f32 = 70.0;
 const RUNNING_SPEED: f32 = 165.0;
 const TURNING_SPEED: f64 = 8.0 * PI;
-const RELOAD_TIME: f32 = 0.3;
+const SHOT_COOLDOWN: f32 = 0.3;
+const RELOAD_TIME: f32 = 2.5;
+const CLIP_SIZE: u32 = 5;
+// Noise level of reloading the crossbow (in dB at 1m)
+const RELOAD_NOISE: f32 = 30.0;
+// Stamina change rates (full bars per minute). Recovery is further shaped by the recovery curve.
+const STANDING_RECOVERY: f32 = 20.0;
+const WALKING_RECOVERY: f32 = 10.0;
+const RUNNING_FATIGUE: f32 = 14.0;
+// Below this stamina the player starts to feel exhausted
+const EXHAUSTION_THRESHOLD: f32 = 0.5;
+// Fraction of the turning speed left when completely exhausted
+const EXHAUSTED_TURNING_FACTOR: f64 = 0.35;
+// How far the aim target wanders when completely exhausted
+const MAX_AIM_WOBBLE: f32 = 40.0;
+// Noise level of gasping for air when completely exhausted (in dB at 1m)
+const HEAVY_BREATH_NOISE: f32 = 25.0;
+// Two clicks within this time are a double click
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(300);
+// Chance that a bolt breaks on impact instead of staying on the ground
+const BOLT_BREAK_CHANCE: f64 = 0.3;
+// Hit points of a fresh player. Zombies don't care, a bite is the end anyway.
+const PLAYER_HP: f32 = 100.0;
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(label_player)
             .add_startup_system(label_shot_audio)
+            .add_startup_system(label_reload_audio)
             .add_startup_system(label_breath_audio)
             .add_startup_system(label_goal)
             .add_startup_system(label_target)
@@ -31,33 +62,66 @@ impl Plugin for PlayerPlugin {
                     .as_physics_system()
                     .run_not_in_state(GameState::Loading),
             )
-            .add_system(apply_fatigue.as_physics_system())
-            .add_system(aim.as_physics_system().run_in_state(GameState::Playing))
             .add_system(
-                set_goal
+                apply_fatigue
+                    .as_physics_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .add_system(
+                breath_noise
                     .as_physics_system()
                     .run_in_state(GameState::Playing),
             )
+            .insert_resource(BreathNoiseTimer(Timer::from_seconds(1.0, true)))
+            .add_system(
+                aim.as_physics_system()
+                    .run_in_state(GameState::Playing)
+                    .run_if(crate_is_closed)
+                    .run_if(map_is_closed)
+                    .run_if(is_not_placing),
+            )
+            .add_system(
+                set_goal
+                    .as_physics_system()
+                    .run_in_state(GameState::Playing)
+                    .run_if(map_is_closed),
+            )
             .add_system(
                 toggle_running
                     .as_physics_system()
-                    .run_in_state(GameState::Playing),
+                    .run_in_state(GameState::Playing)
+                    .run_if(map_is_closed),
             )
             .add_system(
                 player_shoot
                     .as_physics_system()
-                    .run_in_state(GameState::Playing),
+                    .run_in_state(GameState::Playing)
+                    .run_if(crate_is_closed)
+                    .run_if(map_is_closed)
+                    .run_if(is_not_placing),
             )
+            .add_system(reload.as_physics_system().run_in_state(GameState::Playing))
+            .add_system(player_death.run_in_state(GameState::Playing))
             .add_system(setup_bullet.as_physics_system())
             .add_system(damage_bullet)
-            .add_system(place_trap.as_physics_system())
+            .add_system(collect_bolts)
             .add_exit_system(GameState::GameOver, on_restart);
     }
 }
 
+#[derive(AssetCollection)]
+pub struct PlayerAssets {
+    // Maps current stamina to a multiplier of the recovery rate
+    #[asset(path = "StaminaRecoveryCurve.tres")]
+    recovery_curve: Handle<GodotResource>,
+}
+
 #[derive(Debug, Component)]
 pub struct Player {
     pub inventory: Inventory,
+    // Bolts loaded in the crossbow
+    pub clip_count: u32,
+    // Bolts carried in the backpack
     pub ammo_count: u32,
 }
 
@@ -70,7 +134,8 @@ impl Default for Player {
 
         Player {
             inventory,
-            ammo_count: 15,
+            clip_count: CLIP_SIZE,
+            ammo_count: 10,
         }
     }
 }
@@ -91,11 +156,13 @@ struct Goal;
 // Note that the actual direction is wherever the player character is facing.
 // Player need to wait for the character to turn before shooting.
 #[derive(Debug, Component)]
-struct Target;
+pub struct Target;
 
 #[derive(Debug, Component, PartialEq, Eq)]
 pub enum Activity {
     Standing,
+    // Keeping low and still, e.g. while looking at the map. Zombies have a harder time spotting it.
+    Ducking,
     Walking,
     Running,
 }
@@ -103,9 +170,21 @@ pub enum Activity {
 #[derive(Debug, Component)]
 pub struct Stamina(pub f32);
 
+impl Stamina {
+    // How exhausted the player is, from 0 (fine) to 1 (completely out of breath)
+    fn exhaustion(&self) -> f32 {
+        (1.0 - self.0 / EXHAUSTION_THRESHOLD).clamp(0.0, 1.0)
+    }
+}
+
+struct BreathNoiseTimer(Timer);
+
 #[derive(Debug, Component)]
 struct ShotAudio;
 
+#[derive(Debug, Component)]
+struct ReloadAudio;
+
 #[derive(Debug, Component, PartialEq, Eq)]
 enum BreathAudio {
     OutOfBreath,
@@ -117,8 +196,17 @@ enum BreathAudio {
 #[derive(Debug, Component)]
 pub struct Bullet;
 
+// Minimal time between two shots
 #[derive(Debug, Component)]
-pub struct ReloadTimer(Timer);
+pub struct ShotCooldown(Timer);
+
+// Present while the player is loading bolts into the crossbow
+#[derive(Debug, Component)]
+pub struct ReloadTimer(pub Option<Timer>);
+
+// A spent bolt lying on the ground, waiting to be picked up
+#[derive(Debug, Component)]
+pub struct BoltPickup;
 
 fn label_player(mut commands: Commands, entities: Query<(&Name, Entity)>) {
     let player_ent = entities
@@ -130,8 +218,10 @@ fn label_player(mut commands: Commands, entities: Query<(&Name, Entity)>) {
         .entity(player_ent)
         .insert(Player::default())
         .insert(Stamina(1.0))
-        .insert(ReloadTimer(Timer::from_seconds(RELOAD_TIME, false)))
-        .insert(Activity::Standing);
+        .insert(ShotCooldown(Timer::from_seconds(SHOT_COOLDOWN, false)))
+        .insert(ReloadTimer(None))
+        .insert(Activity::Standing)
+        .insert(Hp(PLAYER_HP));
 
     let player_interact_ent = entities
         .iter()
@@ -151,6 +241,15 @@ fn label_shot_audio(mut commands: Commands, entities: Query<(&Name, Entity)>) {
     commands.entity(goal).insert(ShotAudio);
 }
 
+fn label_reload_audio(mut commands: Commands, entities: Query<(&Name, Entity)>) {
+    let audio = entities
+        .iter()
+        .find_map(|(name, ent)| (name.as_str() == "ReloadAudio").then_some(ent))
+        .unwrap();
+
+    commands.entity(audio).insert(ReloadAudio);
+}
+
 fn label_breath_audio(mut commands: Commands, entities: Query<(&Name, Entity)>) {
     for (name, entity) in entities.iter() {
         let component = match name.as_str() {
@@ -187,6 +286,8 @@ fn apply_fatigue(
     mut entities: Query<(&mut Stamina, &Activity)>,
     mut time: SystemDelta,
     state: Res<CurrentState<GameState>>,
+    player_assets: Res<PlayerAssets>,
+    mut resources: ResMut<Assets<GodotResource>>,
 ) {
     let delta = time.delta_seconds();
 
@@ -194,19 +295,44 @@ fn apply_fatigue(
         return;
     }
 
+    let curve = resources.get_mut(&player_assets.recovery_curve).unwrap();
+    let curve = curve.get::<Curve>().unwrap();
+
     for (mut stamina, activity) in entities.iter_mut() {
-        let recovery_time = match activity {
-            Activity::Standing => 20.,
-            Activity::Walking => 10.,
-            Activity::Running => -14.,
+        let recovery = curve.interpolate_baked(stamina.0 as f64) as f32;
+        let rate = match activity {
+            Activity::Standing | Activity::Ducking => STANDING_RECOVERY * recovery,
+            Activity::Walking => WALKING_RECOVERY * recovery,
+            Activity::Running => -RUNNING_FATIGUE,
         };
 
-        let fatigue = delta * (recovery_time / 60.0);
-        stamina.0 += fatigue;
+        stamina.0 += delta * (rate / 60.0);
         stamina.0 = stamina.0.clamp(0.0, 1.0);
     }
 }
 
+fn breath_noise(
+    player: Query<(&Transform2D, &Stamina), With<Player>>,
+    mut timer: ResMut<BreathNoiseTimer>,
+    mut noise: EventWriter<NoiseEvent>,
+    time: Res<Time>,
+) {
+    let (transform, stamina) = player.single();
+
+    timer.0.tick(time.delta());
+    if !timer.0.just_finished() {
+        return;
+    }
+
+    let exhaustion = stamina.exhaustion();
+    if exhaustion > 0.0 {
+        noise.send(NoiseEvent {
+            origin: transform.origin,
+            level: HEAVY_BREATH_NOISE * exhaustion,
+        });
+    }
+}
+
 fn move_player(
     mut player: Query<(&mut ErasedGodotRef, &mut Activity, &Stamina), With<Player>>,
     mut goal: Query<(&Transform2D, &mut ErasedGodotRef), (With<Goal>, Without<Player>)>,
@@ -252,19 +378,25 @@ fn move_player(
     }
 
     let distance = body.transform().origin.distance_to(goal);
+    let turning_speed =
+        TURNING_SPEED * (1.0 - (1.0 - EXHAUSTED_TURNING_FACTOR) * stamina.exhaustion() as f64);
 
     match *activity {
         Activity::Standing => {
             play_breath_audio(BreathAudio::None);
             stop(body);
-            turn_toward(body, target);
+            turn_toward(body, target, turning_speed);
+        }
+        Activity::Ducking => {
+            play_breath_audio(BreathAudio::None);
+            stop(body);
         }
         Activity::Walking => {
             if stamina.0 > 0.5 {
                 play_breath_audio(BreathAudio::None);
             };
 
-            let deviation = turn_toward(body, goal);
+            let deviation = turn_toward(body, goal, turning_speed);
             if deviation.abs() > 1.0 {
                 advance(body, 0.0)
             } else {
@@ -292,7 +424,7 @@ fn move_player(
                 play_breath_audio(BreathAudio::Intensive);
             };
 
-            let deviation = turn_toward(body, goal);
+            let deviation = turn_toward(body, goal, turning_speed);
             if deviation.abs() > 1.0 {
                 advance(body, WALKING_SPEED)
             } else {
@@ -310,7 +442,13 @@ fn move_player(
     };
 }
 
-fn turn_toward(body: TRef<Physics2DDirectBodyState>, goal: Vector2) -> f64 {
+// Where in the desert the mouse points at
+// TODO: Getting mouse position from player seems odd. Isn't there a more obvious way?
+pub fn mouse_position(player: TRef<Node2D>) -> Vector2 {
+    player.get_global_mouse_position()
+}
+
+fn turn_toward(body: TRef<Physics2DDirectBodyState>, goal: Vector2, turning_speed: f64) -> f64 {
     let transform = body.transform();
 
     let goal_relative_position = transform.xform_inv(goal);
@@ -321,7 +459,7 @@ fn turn_toward(body: TRef<Physics2DDirectBodyState>, goal: Vector2) -> f64 {
         return 0.0;
     }
 
-    let turn = -TURNING_SPEED * angle;
+    let turn = -turning_speed * angle;
 
     body.set_angular_velocity(turn);
 
@@ -339,21 +477,41 @@ fn stop(body: TRef<Physics2DDirectBodyState>) {
 
 fn aim(
     mut target: Query<(&mut ErasedGodotRef, &mut Transform2D), (With<Target>, Without<Player>)>,
-    mut player: Query<(&Player, &mut ErasedGodotRef, &mut Activity, &ReloadTimer), Without<Target>>,
+    mut player: Query<
+        (
+            &Player,
+            &mut ErasedGodotRef,
+            &mut Activity,
+            &ShotCooldown,
+            &ReloadTimer,
+            &Stamina,
+        ),
+        Without<Target>,
+    >,
     mut goal: Query<&mut ErasedGodotRef, (Without<Player>, Without<Target>, With<Goal>)>,
+    time: Res<Time>,
 ) {
     let input = Input::godot_singleton();
-    let (player, mut player_reference, mut activity, reload_timer) = player.single_mut();
+    let (player, mut player_reference, mut activity, cooldown, reload_timer, stamina) =
+        player.single_mut();
     let (mut target, mut transform) = target.single_mut();
     let mut goal = goal.single_mut();
     let player_reference = player_reference.get::<Node2D>();
 
-    if input.is_action_pressed("aim", false) && player.ammo_count > 0 && reload_timer.0.finished() {
-        // TODO: Getting mouse position from player seems odd. Isn't there a more obvious way?
-        let mouse_position = player_reference.get_global_mouse_position();
+    if input.is_action_pressed("aim", false)
+        && player.clip_count > 0
+        && cooldown.0.finished()
+        && reload_timer.0.is_none()
+    {
+        let mouse_position = mouse_position(player_reference);
         debug!("New target is {mouse_position:?}");
 
-        transform.origin = mouse_position;
+        // An exhausted player can't hold the aim steady
+        let t = time.seconds_since_startup() as f32;
+        let wobble =
+            Vector2::new((t * 2.3).sin(), (t * 3.1).cos()) * MAX_AIM_WOBBLE * stamina.exhaustion();
+
+        transform.origin = mouse_position + wobble;
         target.get::<Node2D>().set_visible(true);
 
         goal.get::<Node2D>().set_visible(false);
@@ -366,6 +524,7 @@ fn aim(
 fn set_goal(
     mut goal: Query<(&mut Transform2D, &mut ErasedGodotRef), (With<Goal>, Without<Player>)>,
     mut player: Query<(&mut ErasedGodotRef, &mut Activity), With<Player>>,
+    mut last_click: Local<Option<Instant>>,
 ) {
     let input = Input::godot_singleton();
     let (mut player, mut activity) = player.single_mut();
@@ -373,8 +532,7 @@ fn set_goal(
     let player = player.get::<Node2D>();
 
     if input.is_action_pressed("set_goal", false) {
-        // TODO: Getting mouse position from player seems odd. Isn't there a more obvious way?
-        let mouse_position = player.get_global_mouse_position();
+        let mouse_position = mouse_position(player);
         debug!("New goal is {mouse_position:?}");
 
         goal_transform.origin = mouse_position;
@@ -386,6 +544,25 @@ fn set_goal(
         };
         debug!("Now {activity:?}");
     }
+
+    if input.is_action_just_pressed("set_goal", false) {
+        let now = Instant::now();
+
+        if let Some(previous) = last_click.take() {
+            if now.duration_since(previous) < DOUBLE_CLICK_TIME {
+                debug!("Double click.");
+
+                *activity = match *activity {
+                    Activity::Running => Activity::Walking,
+                    _ => Activity::Running,
+                };
+                debug!("Now {activity:?}");
+                return;
+            }
+        }
+
+        *last_click = Some(now);
+    }
 }
 
 fn toggle_running(mut activity: Query<&mut Activity, With<Player>>) {
@@ -404,46 +581,127 @@ fn toggle_running(mut activity: Query<&mut Activity, With<Player>>) {
 fn player_shoot(
     mut commands: Commands,
     mut target: Query<&mut ErasedGodotRef, With<Target>>,
-    mut player: Query<(&mut Player, &Transform2D, &mut Activity, &mut ReloadTimer)>,
+    mut player: Query<(
+        &mut Player,
+        &Transform2D,
+        &mut Activity,
+        &mut ShotCooldown,
+        &mut ReloadTimer,
+    )>,
+    mut reload_audio: Query<
+        &mut ErasedGodotRef,
+        (With<ReloadAudio>, Without<Target>, Without<ShotAudio>),
+    >,
+    mut shot_audio: Query<&mut ErasedGodotRef, (With<ShotAudio>, Without<Target>)>,
+    mut noise: EventWriter<NoiseEvent>,
     time: Res<Time>,
 ) {
     let input = Input::godot_singleton();
-    let (mut player, player_transform, mut activity, mut reloading) = player.single_mut();
+    let (mut player, player_transform, mut activity, mut cooldown, mut reload_timer) =
+        player.single_mut();
     let mut target = target.single_mut();
 
     // Update the timer
-    reloading.0.tick(time.delta());
+    cooldown.0.tick(time.delta());
 
     if input.is_action_just_released("aim", false)
-        && player.ammo_count > 0
-        && reloading.0.finished()
+        && player.clip_count > 0
+        && cooldown.0.finished()
+        && reload_timer.0.is_none()
     {
         debug!("Shoot!");
-        reloading.0.reset();
+        cooldown.0.reset();
 
-        let bullet_transform = *player_transform;
-        commands
-            .spawn()
-            .insert(GodotScene::from_path("res://Bullet.tscn"))
-            .insert(Bullet)
-            .insert(bullet_transform);
+        spawn_bullet(&mut commands, *player_transform);
+        shot_audio.single_mut().get::<AudioStreamPlayer>().play(0.0);
 
-        player.ammo_count -= 1;
+        player.clip_count -= 1;
 
         target.get::<Node2D>().set_visible(false);
         *activity = Activity::Standing;
         debug!("Now {activity:?}");
+
+        // Reload automatically once the clip is empty
+        if player.clip_count == 0 && player.ammo_count > 0 {
+            start_reload(
+                &mut reload_timer,
+                &mut reload_audio.single_mut(),
+                player_transform.origin,
+                &mut noise,
+            );
+        }
     }
 }
 
-fn setup_bullet(
-    mut bullets: Query<(&mut ErasedGodotRef, &Transform2D), Added<Bullet>>,
-    mut audio: Query<&mut ErasedGodotRef, (With<ShotAudio>, Without<Bullet>)>,
+fn start_reload(
+    reload_timer: &mut ReloadTimer,
+    audio: &mut ErasedGodotRef,
+    origin: Vector2,
+    noise: &mut EventWriter<NoiseEvent>,
 ) {
-    for (mut bullet, bullet_transform) in bullets.iter_mut() {
-        let mut audio = audio.single_mut();
-        audio.get::<AudioStreamPlayer>().play(0.0);
+    debug!("Reloading.");
+    reload_timer.0 = Some(Timer::from_seconds(RELOAD_TIME, false));
+    audio.get::<AudioStreamPlayer>().play(0.0);
+    noise.send(NoiseEvent {
+        origin,
+        level: RELOAD_NOISE,
+    });
+}
+
+fn reload(
+    mut player: Query<(&mut Player, &Transform2D, &Activity, &mut ReloadTimer)>,
+    mut audio: Query<&mut ErasedGodotRef, With<ReloadAudio>>,
+    mut noise: EventWriter<NoiseEvent>,
+    mut item_log: EventWriter<ItemLogEvent>,
+    time: Res<Time>,
+) {
+    let input = Input::godot_singleton();
+    let (mut player, player_transform, activity, mut reload_timer) = player.single_mut();
+    let mut audio = audio.single_mut();
+
+    if let Some(timer) = reload_timer.0.as_mut() {
+        if *activity == Activity::Running {
+            debug!("Reload interrupted by sprinting.");
+            item_log.send(ItemLogEvent("Reload interrupted".to_string()));
+            audio.get::<AudioStreamPlayer>().stop();
+            reload_timer.0 = None;
+            return;
+        }
+
+        timer.tick(time.delta());
+        if timer.finished() {
+            let loaded = u32::min(CLIP_SIZE - player.clip_count, player.ammo_count);
+            player.clip_count += loaded;
+            player.ammo_count -= loaded;
+            debug!("Reloaded {loaded} bolts.");
+
+            reload_timer.0 = None;
+        }
+    } else if input.is_action_just_pressed("reload", false)
+        && *activity != Activity::Running
+        && player.clip_count < CLIP_SIZE
+        && player.ammo_count > 0
+    {
+        start_reload(
+            &mut reload_timer,
+            &mut audio,
+            player_transform.origin,
+            &mut noise,
+        );
+    }
+}
+
+// Fires a bolt the way the transform is facing. Used by the player's crossbow and by turrets.
+pub fn spawn_bullet(commands: &mut Commands, transform: Transform2D) {
+    commands
+        .spawn()
+        .insert(GodotScene::from_path("res://Bullet.tscn"))
+        .insert(Bullet)
+        .insert(transform);
+}
 
+fn setup_bullet(mut bullets: Query<(&mut ErasedGodotRef, &Transform2D), Added<Bullet>>) {
+    for (mut bullet, bullet_transform) in bullets.iter_mut() {
         let bullet = bullet.get::<RigidBody2D>();
         let bullet_velocity = bullet_transform.basis_xform_inv(Vector2::new(0.0, -800.0));
         bullet.set_linear_velocity(bullet_velocity);
@@ -451,10 +709,11 @@ fn setup_bullet(
 }
 
 fn damage_bullet(
-    mut bullets: Query<(&Collisions, &mut ErasedGodotRef), With<Bullet>>,
+    mut commands: Commands,
+    mut bullets: Query<(&Collisions, &Transform2D, &mut ErasedGodotRef), With<Bullet>>,
     mut zombies: Query<&mut Hp, With<Zombie>>,
 ) {
-    for (collisions, mut bullet) in bullets.iter_mut() {
+    for (collisions, bullet_transform, mut bullet) in bullets.iter_mut() {
         if collisions.recent_collisions().is_empty() {
             continue;
         }
@@ -466,53 +725,77 @@ fn damage_bullet(
 
         let bullet = bullet.get::<Node>();
         bullet.queue_free();
+
+        if rand::random::<f64>() < BOLT_BREAK_CHANCE {
+            debug!("The bolt broke on impact.");
+            continue;
+        }
+
+        // Leave the bolt where it hit, so it can be recovered
+        commands
+            .spawn()
+            .insert(GodotScene::from_path("res://BoltPickup.tscn"))
+            .insert(BoltPickup)
+            .insert(*bullet_transform);
     }
 }
 
-fn place_trap(
-    mut commands: Commands,
-    mut player: Query<(&mut Player, &Transform2D)>,
-    selected_slot: Res<SelectedItemSlot>,
+fn collect_bolts(
+    player_interact_volume: Query<&Collisions, With<PlayerInteractVolume>>,
+    mut player: Query<&mut Player>,
+    mut bolts: Query<&mut ErasedGodotRef, With<BoltPickup>>,
+    mut item_log: EventWriter<ItemLogEvent>,
 ) {
-    let input = Input::godot_singleton();
+    let player_interact_volume = player_interact_volume.single();
 
-    if input.is_action_just_pressed("place_trap", false) {
-        let (mut player, player_transform) = player.single_mut();
-
-        if let Some(slot) = selected_slot.0 {
-            let mut items = player
-                .inventory
-                .get_items()
-                .iter()
-                .filter(|(_, count)| **count > 0)
-                .skip(slot as usize);
-            if let Some((item, _count)) = items.next().map(|(item, count)| (*item, *count)) {
-                player.inventory.use_item(&item);
-
-                commands
-                    .spawn()
-                    .insert(GodotScene::from_path(item.scene_path()))
-                    .insert(Transform2D(
-                        GodotTransform2D::IDENTITY.translated(player_transform.origin),
-                    ));
-            }
+    for ent in player_interact_volume.recent_collisions() {
+        if let Ok(mut bolt) = bolts.get_mut(*ent) {
+            bolt.get::<Node>().queue_free();
+
+            let mut player = player.single_mut();
+            player.ammo_count += 1;
+
+            item_log.send(ItemLogEvent("Picked up a bolt".to_string()));
         }
     }
 }
 
+fn player_death(mut commands: Commands, player: Query<&Hp, With<Player>>) {
+    if player.single().0 <= 0.0 {
+        debug!("You died!");
+        commands.insert_resource(NextState(GameState::GameOver));
+    }
+}
+
 fn on_restart(
-    mut player: Query<(&mut Player, &mut Activity, &mut Stamina)>,
+    mut player: Query<(
+        &mut Player,
+        &mut Activity,
+        &mut Stamina,
+        &mut ShotCooldown,
+        &mut ReloadTimer,
+        &mut Hp,
+    )>,
     mut goal: Query<&mut ErasedGodotRef, (With<Goal>, Without<Target>)>,
     mut target: Query<&mut ErasedGodotRef, (With<Target>, Without<Goal>)>,
+    mut bolts: Query<&mut ErasedGodotRef, (With<BoltPickup>, Without<Goal>, Without<Target>)>,
 ) {
-    let (mut player, mut activity, mut stamina) = player.single_mut();
+    let (mut player, mut activity, mut stamina, mut cooldown, mut reload_timer, mut hp) =
+        player.single_mut();
     let mut goal = goal.single_mut();
     let mut target = target.single_mut();
 
     player.reset();
     *activity = Activity::Standing;
     stamina.0 = 1.0;
+    cooldown.0.reset();
+    reload_timer.0 = None;
+    hp.0 = PLAYER_HP;
 
     goal.get::<Node2D>().set_visible(false);
     target.get::<Node2D>().set_visible(false);
+
+    for mut bolt in bolts.iter_mut() {
+        bolt.get::<Node>().queue_free();
+    }
 }
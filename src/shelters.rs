@@ -0,0 +1,180 @@
+use crate::{
+    airdrops::crate_is_closed,
+    map::{map_is_closed, MapTarget},
+    placement::is_not_placing,
+    player::{Activity, Player},
+    traps::fire::{extinguish, Burning},
+    ui::{offscreen_indicator::OffscreenTarget, text_log::ItemLogEvent},
+    GameState,
+};
+use bevy::log::*;
+use bevy_asset_loader::prelude::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, EventWriter, With, Without},
+    godot_prelude::{Color, Vector2},
+    *,
+};
+use iyes_loopless::prelude::*;
+
+// How close to a shelter's hatch the player has to be to climb in
+const ENTRY_RADIUS: f32 = 100.0;
+
+pub struct SheltersPlugin;
+impl Plugin for SheltersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(label_shelters)
+            .add_system(mark_shelters.run_not_in_state(GameState::Loading))
+            .add_system(
+                enter_shelter
+                    .run_in_state(GameState::Playing)
+                    .run_if(crate_is_closed)
+                    .run_if(map_is_closed)
+                    .run_if(is_not_placing),
+            )
+            .add_system(leave_shelter.run_in_state(GameState::Sheltered))
+            .add_enter_system(GameState::Sheltered, hide_player)
+            .add_exit_system(GameState::Sheltered, climb_out);
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct ShelterAssets {
+    // TODO: Draw a proper shelter icon
+    #[asset(path = "art/tool.svg")]
+    pub icon: Handle<GodotResource>,
+}
+
+// A bunker in the desert. The player can hide in it, rest and craft.
+#[derive(Debug, Component)]
+pub struct Shelter {
+    // Where the player climbs in and out
+    hatch: Vector2,
+}
+
+impl Shelter {
+    pub fn hatch(&self) -> Vector2 {
+        self.hatch
+    }
+}
+
+// The player is hiding in this shelter. Zombies can't see, hear or hurt them there.
+#[derive(Debug, Component)]
+pub struct InShelter(Entity);
+
+fn label_shelters(
+    mut commands: Commands,
+    mut entities: Query<(&Groups, Entity, &mut ErasedGodotRef), Added<ErasedGodotRef>>,
+) {
+    for (groups, ent, mut reference) in entities.iter_mut() {
+        if !groups.is("shelter") {
+            continue;
+        }
+
+        let hatch = reference.get::<Node>().get_node("Hatch").and_then(|hatch| {
+            unsafe { hatch.assume_safe() }
+                .cast::<Node2D>()
+                .map(|hatch| hatch.global_position())
+        });
+
+        match hatch {
+            Some(hatch) => {
+                commands.entity(ent).insert(Shelter { hatch });
+            }
+            None => error!("Shelter has no Node2D at Hatch"),
+        }
+    }
+}
+
+// Shelters are always worth knowing the way to
+fn mark_shelters(
+    mut commands: Commands,
+    shelters: Query<Entity, (With<Shelter>, Without<OffscreenTarget>)>,
+    shelter_assets: Res<ShelterAssets>,
+) {
+    for ent in shelters.iter() {
+        commands
+            .entity(ent)
+            .insert(OffscreenTarget {
+                icon: shelter_assets.icon.clone(),
+                color: Color::from_rgba(0.6, 0.8, 0.6, 0.5),
+                tooltip: "Shelter".to_string(),
+            })
+            .insert(MapTarget {
+                icon: shelter_assets.icon.clone(),
+                color: Color::from_rgba(0.6, 0.8, 0.6, 1.0),
+                tooltip: "Shelter".to_string(),
+            });
+    }
+}
+
+fn enter_shelter(
+    mut commands: Commands,
+    player: Query<(Entity, &Transform2D), With<Player>>,
+    shelters: Query<(Entity, &Shelter)>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
+
+    if !input.is_action_just_pressed("enter_shelter", false) {
+        return;
+    }
+
+    let (player, player_transform) = player.single();
+    let shelter = shelters
+        .iter()
+        .find(|(_, shelter)| shelter.hatch.distance_to(player_transform.origin) < ENTRY_RADIUS);
+
+    match shelter {
+        Some((shelter, _)) => {
+            debug!("Entering shelter {:?}", shelter);
+            commands.entity(player).insert(InShelter(shelter));
+            commands.insert_resource(NextState(GameState::Sheltered));
+        }
+        None => item_log.send(ItemLogEvent("There's no shelter nearby".to_string())),
+    }
+}
+
+fn leave_shelter(mut commands: Commands) {
+    let input = Input::godot_singleton();
+
+    if input.is_action_just_pressed("enter_shelter", false)
+        || input.is_action_just_pressed("ui_cancel", false)
+    {
+        commands.insert_resource(NextState(GameState::Playing));
+    }
+}
+
+// Takes the player out of the world while they are inside
+fn hide_player(
+    mut commands: Commands,
+    mut player: Query<(Entity, &mut ErasedGodotRef, Option<&Burning>), With<Player>>,
+) {
+    let (ent, mut reference, burning) = player.single_mut();
+
+    // climbing in puts the flames out
+    if burning.is_some() {
+        extinguish(&mut commands, ent, reference.get::<Node>());
+    }
+
+    let body = reference.get::<RigidBody2D>();
+    body.set_visible(false);
+    body.set_collision_layer(0);
+}
+
+fn climb_out(
+    mut commands: Commands,
+    mut player: Query<(Entity, &InShelter, &mut ErasedGodotRef, &mut Activity), With<Player>>,
+    shelters: Query<&Shelter>,
+) {
+    let (ent, InShelter(shelter), mut reference, mut activity) = player.single_mut();
+    let hatch = shelters.get(*shelter).unwrap().hatch;
+    debug!("Leaving shelter {:?}", shelter);
+
+    let body = reference.get::<RigidBody2D>();
+    body.set_global_position(hatch);
+    body.set_visible(true);
+    body.set_collision_layer(1);
+
+    *activity = Activity::Standing;
+    commands.entity(ent).remove::<InShelter>();
+}
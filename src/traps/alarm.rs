@@ -1,22 +1,40 @@
-use crate::GameState;
+use super::{AddTrap, Trap, TrapNodes, TrapState, UsedBattery};
+use crate::{
+    crafting::{AlarmTiming, Item},
+    map::MapTarget,
+    ui::text_log::ItemLogEvent,
+    GameState,
+};
+use bevy::log::*;
 use bevy_godot::prelude::{
-    bevy_prelude::{Added, With, Without},
+    bevy_prelude::{Added, EventWriter, Or, Without},
+    godot_prelude::Color,
     *,
 };
 use iyes_loopless::prelude::*;
+use std::time::Duration;
 
 pub struct AlarmPlugin;
 impl Plugin for AlarmPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(label_alarms)
+        app.add_trap::<Alarm>()
+            .add_trap::<TimedAlarm>()
+            .add_trap::<RemoteAlarm>()
+            .add_system(wire_alarm::<TimedAlarm>)
+            .add_system(wire_alarm::<RemoteAlarm>)
+            .add_system(restore_used_batteries)
+            .add_system(trigger_remote_alarms.run_in_state(GameState::Playing))
             .add_system(process_alarms)
-            .add_exit_system(GameState::GameOver, on_restart);
+            .add_system(show_alarm_state);
     }
 }
 
+// Every alarm device rings the same way. They only differ in timing, which comes with the recipe.
 #[derive(Debug, Component)]
 pub struct Alarm {
-    lifetime_timer: Timer,
+    // Counting down to the first ring. Remote alarms have none until the player sets them off.
+    countdown: Option<Timer>,
+    battery: Timer,
     active_period: Timer,
     inactive_period: Timer,
     is_active: bool,
@@ -24,79 +42,194 @@ pub struct Alarm {
 
 impl Default for Alarm {
     fn default() -> Self {
-        Self {
-            lifetime_timer: Timer::from_seconds(60.0, false),
-            active_period: Timer::from_seconds(15.0, true),
-            inactive_period: Timer::from_seconds(5.0, true),
-            is_active: true,
-        }
+        Self::new(Item::Alarm.alarm_timing().unwrap())
     }
 }
 
 impl Alarm {
+    fn new(timing: AlarmTiming) -> Self {
+        Self {
+            countdown: timing
+                .start_delay
+                .map(|delay| Timer::from_seconds(delay, false)),
+            battery: Timer::from_seconds(timing.battery_life, false),
+            active_period: Timer::from_seconds(timing.ring_period, true),
+            inactive_period: Timer::from_seconds(timing.quiet_period, true),
+            is_active: false,
+        }
+    }
+
     pub fn is_active(&self) -> bool {
         self.is_active
     }
+
+    // How much battery is left, from 0 (dead) to 1 (fresh)
+    pub fn battery_level(&self) -> f32 {
+        1.0 - self.battery.percent()
+    }
+
+    pub fn remaining_battery(&self) -> Duration {
+        self.battery
+            .duration()
+            .saturating_sub(self.battery.elapsed())
+    }
 }
 
-#[derive(Debug, Component)]
-pub struct AlarmAudioPlayer(Entity);
+impl Trap for Alarm {
+    const ITEM: Item = Item::Alarm;
+    const SCENE: &'static str = "res://traps/Alarm.tscn";
+    const GROUP: &'static str = "alarm";
+    const NODES: &'static [&'static str] = &["AudioStreamPlayer2D", "Battery"];
+
+    fn indicator_color() -> Option<Color> {
+        Some(Color::from_rgba(0.9, 0.3, 0.3, 0.5))
+    }
+
+    fn map_color() -> Option<Color> {
+        Some(Color::from_rgba(0.9, 0.3, 0.3, 1.0))
+    }
+
+    fn can_pick_up(&self, state: TrapState) -> bool {
+        alarm_can_pick_up(state)
+    }
+}
+
+#[derive(Debug, Component, Default)]
+pub struct TimedAlarm;
+
+impl Trap for TimedAlarm {
+    const ITEM: Item = Item::TimedAlarm;
+    const SCENE: &'static str = "res://traps/TimedAlarm.tscn";
+    const GROUP: &'static str = "timed_alarm";
+    const NODES: &'static [&'static str] = Alarm::NODES;
+
+    fn indicator_color() -> Option<Color> {
+        Alarm::indicator_color()
+    }
+
+    fn map_color() -> Option<Color> {
+        Alarm::map_color()
+    }
+
+    fn can_pick_up(&self, state: TrapState) -> bool {
+        alarm_can_pick_up(state)
+    }
+}
+
+#[derive(Debug, Component, Default)]
+pub struct RemoteAlarm;
+
+impl Trap for RemoteAlarm {
+    const ITEM: Item = Item::RemoteAlarm;
+    const SCENE: &'static str = "res://traps/RemoteAlarm.tscn";
+    const GROUP: &'static str = "remote_alarm";
+    const NODES: &'static [&'static str] = Alarm::NODES;
+
+    fn indicator_color() -> Option<Color> {
+        Alarm::indicator_color()
+    }
 
-fn label_alarms(
-    mut commands: Commands,
-    mut entities: Query<(&Groups, Entity, &mut ErasedGodotRef), Added<ErasedGodotRef>>,
+    fn map_color() -> Option<Color> {
+        Alarm::map_color()
+    }
+
+    fn can_pick_up(&self, state: TrapState) -> bool {
+        alarm_can_pick_up(state)
+    }
+}
+
+// Alarms can be taken back even while ringing, with whatever is left of the battery
+fn alarm_can_pick_up(state: TrapState) -> bool {
+    state != TrapState::Expired
+}
+
+// Gives alarm devices an alarm timed the way their recipe says
+fn wire_alarm<T: Trap>(mut commands: Commands, devices: Query<Entity, Added<T>>) {
+    for ent in devices.iter() {
+        let timing = T::ITEM
+            .alarm_timing()
+            .unwrap_or_else(|| panic!("{:?} has no alarm timing", T::ITEM));
+        commands.entity(ent).insert(Alarm::new(timing));
+    }
+}
+
+// A device that was picked up and placed again keeps its old battery
+fn restore_used_batteries(
+    mut alarms: Query<(&mut Alarm, &UsedBattery), Or<(Added<Alarm>, Added<UsedBattery>)>>,
 ) {
-    let mut alarms = vec![];
-    for (groups, ent, mut reference) in entities.iter_mut() {
-        if groups.is("alarm") {
-            commands.entity(ent).insert(Alarm::default());
-
-            let audio_player_instance_id = unsafe {
-                reference
-                    .get::<Node2D>()
-                    .get_node("AudioStreamPlayer2D")
-                    .unwrap()
-                    .assume_safe()
-                    .get_instance_id()
-            };
-            alarms.push((ent, audio_player_instance_id));
-        }
+    for (mut alarm, UsedBattery(remaining)) in alarms.iter_mut() {
+        let elapsed = alarm.battery.duration().saturating_sub(*remaining);
+        alarm.battery.set_elapsed(elapsed);
     }
+}
 
-    for (alarm_ent, audio_player_instance_id) in alarms {
-        let audio_player = entities
-            .iter()
-            .find_map(|(_, ent, reference)| {
-                (reference.instance_id() == audio_player_instance_id).then_some(ent)
-            })
-            .unwrap();
+// Sets off every remote alarm that's still waiting
+fn trigger_remote_alarms(
+    mut alarms: Query<(&mut Alarm, &TrapState)>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
 
-        commands
-            .entity(audio_player)
-            .insert(AlarmAudioPlayer(alarm_ent));
+    if !input.is_action_just_pressed("trigger_remote_alarms", false) {
+        return;
+    }
+
+    let mut triggered = 0;
+    for (mut alarm, state) in alarms.iter_mut() {
+        if *state == TrapState::Placed && alarm.countdown.is_none() {
+            alarm.countdown = Some(Timer::from_seconds(0.0, false));
+            triggered += 1;
+        }
+    }
+
+    if triggered > 0 {
+        item_log.send(ItemLogEvent(format!("Set off {triggered} remote alarm(s)")));
     }
 }
 
 fn process_alarms(
-    mut alarms: Query<(&mut Alarm, &mut ErasedGodotRef, Entity)>,
-    mut alarm_sfx_players: Query<(&AlarmAudioPlayer, &mut ErasedGodotRef), Without<Alarm>>,
+    mut alarms: Query<(&mut Alarm, &mut TrapState, &TrapNodes)>,
+    mut nodes: Query<&mut ErasedGodotRef, Without<Alarm>>,
     mut time: SystemDelta,
 ) {
     let delta = time.delta();
 
-    for (mut alarm, mut reference, alarm_ent) in alarms.iter_mut() {
-        let reference = reference.get::<Node2D>();
+    for (mut alarm, mut state, alarm_nodes) in alarms.iter_mut() {
+        if *state == TrapState::Expired {
+            continue;
+        }
+
+        let mut battery = nodes.get_mut(alarm_nodes.get("Battery")).unwrap();
+        battery
+            .get::<ProgressBar>()
+            .set_value((alarm.battery_level() * 100.0).into());
 
-        let mut sound = alarm_sfx_players
-            .iter_mut()
-            .find_map(|(audio, reference)| (audio.0 == alarm_ent).then_some(reference))
+        let mut sound = nodes
+            .get_mut(alarm_nodes.get("AudioStreamPlayer2D"))
             .unwrap();
         let sound = sound.get::<AudioStreamPlayer2D>();
 
-        alarm.lifetime_timer.tick(delta);
-        if alarm.lifetime_timer.finished() {
-            reference.queue_free();
-            return;
+        if *state == TrapState::Placed {
+            let countdown = match alarm.countdown.as_mut() {
+                Some(countdown) => countdown,
+                None => continue,
+            };
+
+            countdown.tick(delta);
+            if countdown.finished() {
+                debug!("Alarm started");
+                *state = TrapState::Triggered;
+                alarm.is_active = true;
+                sound.play(0.0);
+            }
+            continue;
+        }
+
+        alarm.battery.tick(delta);
+        if alarm.battery.finished() {
+            alarm.is_active = false;
+            *state = TrapState::Expired;
+            continue;
         }
 
         if alarm.is_active {
@@ -117,8 +250,16 @@ fn process_alarms(
     }
 }
 
-fn on_restart(mut alarms: Query<&mut ErasedGodotRef, With<Alarm>>) {
-    for mut alarm in alarms.iter_mut() {
-        alarm.get::<Node2D>().queue_free();
+// The map tells ringing alarms from quiet ones
+fn show_alarm_state(mut alarms: Query<(&Alarm, &mut MapTarget)>) {
+    for (alarm, mut map_target) in alarms.iter_mut() {
+        let tooltip = if alarm.is_active {
+            "Alarm (ringing)"
+        } else {
+            "Alarm (quiet)"
+        };
+        if map_target.tooltip != tooltip {
+            map_target.tooltip = tooltip.to_string();
+        }
     }
 }
@@ -0,0 +1,125 @@
+use crate::{
+    airdrops::{drop_airdrop, AirDrop, BonusAirDrop},
+    crafting::Part,
+    player::Player,
+    shelters::InShelter,
+    ui::text_log::ItemLogEvent,
+    Hp,
+};
+use bevy::log::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{EventReader, EventWriter, With, Without},
+    godot_prelude::Vector2,
+    *,
+};
+use std::{f32::consts::PI, iter};
+
+pub struct ExplosionPlugin;
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExplosionEvent>()
+            .add_system(explosion_damage);
+    }
+}
+
+// How hard an explosion hits
+#[derive(Debug, Clone, Copy)]
+pub struct Blast {
+    pub radius: f32,
+    pub power: f32,
+    // Only flying shrapnel is dangerous to the player, who knows to keep clear of the rest
+    pub hurts_player: bool,
+}
+
+impl Blast {
+    pub fn at(&self, origin: Vector2) -> ExplosionEvent {
+        ExplosionEvent {
+            origin,
+            radius: self.radius,
+            power: self.power,
+            hurts_player: self.hurts_player,
+        }
+    }
+}
+
+// Something blew up. Anything with hit points takes damage, falling off linearly from `power` at
+// the origin to nothing at the edge of the radius. Traps within the radius may react to it too.
+// The player is spared unless `hurts_player` is set.
+#[derive(Debug, Clone, Copy)]
+pub struct ExplosionEvent {
+    pub origin: Vector2,
+    pub radius: f32,
+    pub power: f32,
+    pub hurts_player: bool,
+}
+
+impl ExplosionEvent {
+    pub fn reaches(&self, position: Vector2) -> bool {
+        position.distance_to(self.origin) < self.radius
+    }
+
+    pub fn damage_at(&self, position: Vector2) -> f32 {
+        (self.power * (1.0 - position.distance_to(self.origin) / self.radius)).max(0.0)
+    }
+}
+
+fn explosion_damage(
+    mut commands: Commands,
+    mut explosions: EventReader<ExplosionEvent>,
+    mut entities: Query<(Entity, &mut Hp, &Transform2D), Without<InShelter>>,
+    player: Query<(Entity, &Transform2D), With<Player>>,
+    mut log: EventWriter<ItemLogEvent>,
+) {
+    let explosions = explosions.iter().collect::<Vec<_>>();
+    if explosions.is_empty() {
+        return;
+    }
+
+    let (player_ent, player_transform) = player.single();
+    let mut killed_zombies = 0;
+
+    // everything that goes off in the same frame counts as one big bang
+    for (ent, mut obj_hp, transform) in entities.iter_mut() {
+        let damage = explosions
+            .iter()
+            .filter(|explosion| ent != player_ent || explosion.hurts_player)
+            .map(|explosion| explosion.damage_at(transform.origin))
+            .sum::<f32>();
+        if damage <= 0.0 || obj_hp.0 <= 0.0 {
+            continue;
+        }
+
+        obj_hp.0 -= damage;
+
+        if ent == player_ent {
+            log.send(ItemLogEvent("Caught in the blast!".to_string()));
+        } else if obj_hp.0 <= 0.0 {
+            killed_zombies += 1;
+        }
+    }
+
+    if killed_zombies > 5 {
+        log.send(ItemLogEvent(format!(
+            "{}x Killing Spree! An extra airdrop is on the way!",
+            killed_zombies
+        )));
+
+        let mut airdrop_transform = *player_transform;
+
+        airdrop_transform.set_rotation(rand::random::<f32>() * 2.0 * PI);
+        airdrop_transform.0 = airdrop_transform.translated(Vector2::UP * 1000.0);
+        airdrop_transform.set_rotation(0.0);
+
+        let airdrop = drop_airdrop(
+            &mut commands,
+            airdrop_transform.origin,
+            AirDrop::new(
+                iter::repeat_with(|| Part::random())
+                    .take(5)
+                    .collect::<Vec<_>>(),
+            ),
+        );
+        commands.entity(airdrop).insert(BonusAirDrop);
+        debug!("Bonus airdrop for killing {killed_zombies} zombies");
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{player::Player, ui::text_log::ItemLogEvent, GameState, Hp};
+use bevy::{
+    ecs::{system::Command, world::World},
+    log::*,
+};
+use bevy_asset_loader::prelude::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, EventWriter, With, Without},
+    *,
+};
+use iyes_loopless::prelude::*;
+
+// How long something keeps burning (in seconds)
+const BURN_TIME: f32 = 6.0;
+// Hit points lost per second while on fire
+const BURN_DAMAGE: f32 = 2.0;
+// After burning for this long, the flames jump over to flammable things nearby
+const SPREAD_DELAY: f32 = 2.0;
+const SPREAD_DISTANCE: f32 = 200.0;
+
+pub struct FirePlugin;
+impl Plugin for FirePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            show_flames
+                .as_visual_system()
+                .run_not_in_state(GameState::Loading),
+        )
+        .add_system(burn.run_in_state(GameState::Playing))
+        .add_exit_system(GameState::GameOver, on_restart);
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct FireAssets {
+    #[asset(path = "Fire.tscn")]
+    fire_scene: Handle<GodotResource>,
+}
+
+// Catches fire from burning things nearby. Burns down to nothing, unless it has hit points.
+#[derive(Debug, Component)]
+pub struct Flammable;
+
+#[derive(Debug, Component)]
+pub struct Burning {
+    burn_out: Timer,
+    spread: Timer,
+}
+
+impl Default for Burning {
+    fn default() -> Self {
+        Self {
+            burn_out: Timer::from_seconds(BURN_TIME, false),
+            spread: Timer::from_seconds(SPREAD_DELAY, false),
+        }
+    }
+}
+
+// Sets something on fire, or keeps it burning for longer
+pub fn ignite(commands: &mut Commands, ent: Entity) {
+    commands.add(Ignite(ent));
+}
+
+struct Ignite(Entity);
+
+impl Command for Ignite {
+    fn write(self, world: &mut World) {
+        let mut entity = match world.get_entity_mut(self.0) {
+            Some(entity) => entity,
+            None => return,
+        };
+
+        // only the flames last longer, so fire keeps spreading on schedule
+        match entity.get_mut::<Burning>() {
+            Some(mut burning) => burning.burn_out.reset(),
+            None => {
+                entity.insert(Burning::default());
+            }
+        }
+    }
+}
+
+fn show_flames(
+    mut burning: Query<(&mut ErasedGodotRef, Option<&Player>), Added<Burning>>,
+    fire_assets: Res<FireAssets>,
+    assets: Res<Assets<GodotResource>>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    for (mut reference, player) in burning.iter_mut() {
+        let node = reference.get::<Node>();
+        if node.has_node("Fire") {
+            continue;
+        }
+
+        let scene = assets
+            .get(&fire_assets.fire_scene)
+            .unwrap()
+            .0
+            .clone()
+            .cast::<PackedScene>()
+            .unwrap();
+        let fire = unsafe { scene.assume_safe() }
+            .instance(PackedScene::GEN_EDIT_STATE_DISABLED)
+            .unwrap();
+        node.add_child(fire, false);
+
+        if player.is_some() {
+            item_log.send(ItemLogEvent("You're on fire!".to_string()));
+        }
+    }
+}
+
+fn burn(
+    mut commands: Commands,
+    mut burning: Query<(
+        Entity,
+        &mut Burning,
+        &Transform2D,
+        Option<&mut Hp>,
+        &mut ErasedGodotRef,
+    )>,
+    flammables: Query<(Entity, &Transform2D), (With<Flammable>, Without<Burning>)>,
+    mut time: SystemDelta,
+) {
+    let delta = time.delta();
+
+    for (ent, mut burning, transform, mut hp, mut reference) in burning.iter_mut() {
+        if let Some(hp) = hp.as_mut() {
+            hp.0 -= BURN_DAMAGE * delta.as_secs_f32();
+        }
+
+        burning.spread.tick(delta);
+        if burning.spread.just_finished() {
+            for (flammable, flammable_transform) in flammables.iter() {
+                if flammable_transform.origin.distance_to(transform.origin) < SPREAD_DISTANCE {
+                    debug!("Fire spread from {:?} to {:?}", ent, flammable);
+                    ignite(&mut commands, flammable);
+                }
+            }
+        }
+
+        burning.burn_out.tick(delta);
+        if burning.burn_out.just_finished() {
+            let node = reference.get::<Node>();
+            if hp.is_some() {
+                // the living survive, if they have enough hit points left
+                extinguish(&mut commands, ent, node);
+            } else {
+                debug!("{:?} burnt down", ent);
+                node.queue_free();
+            }
+        }
+    }
+}
+
+pub fn extinguish(commands: &mut Commands, ent: Entity, node: TRef<Node>) {
+    if let Some(fire) = node.get_node_or_null("Fire") {
+        unsafe { fire.assume_safe() }.queue_free();
+    }
+    commands.entity(ent).remove::<Burning>();
+}
+
+fn on_restart(
+    mut commands: Commands,
+    mut burning: Query<(Entity, &mut ErasedGodotRef), (With<Burning>, With<Hp>)>,
+) {
+    for (ent, mut reference) in burning.iter_mut() {
+        extinguish(&mut commands, ent, reference.get::<Node>());
+    }
+}
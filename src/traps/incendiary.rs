@@ -0,0 +1,79 @@
+use super::{
+    explosion::ExplosionEvent,
+    fire::{ignite, Burning, Flammable},
+    AddTrap, Trap, TrapState,
+};
+use crate::{crafting::Item, shelters::InShelter, Hp};
+use bevy::{ecs::system::EntityCommands, log::*};
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, EventReader, Or, With, Without},
+    godot_prelude::Color,
+    *,
+};
+
+// Everything this close to an incendiary device catches fire when it goes up in flames
+const FLAME_RADIUS: f32 = 350.0;
+
+pub struct IncendiaryPlugin;
+impl Plugin for IncendiaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_trap::<Incendiary>()
+            .add_system(blown_up_incendiaries)
+            .add_system(process_incendiaries);
+    }
+}
+
+// Doesn't go off on its own. It needs a bomb nearby, or another fire.
+#[derive(Debug, Component, Default)]
+pub struct Incendiary;
+
+impl Trap for Incendiary {
+    const ITEM: Item = Item::Incendiary;
+    const SCENE: &'static str = "res://traps/Incendiary.tscn";
+    const GROUP: &'static str = "incendiary";
+
+    fn map_color() -> Option<Color> {
+        Some(Color::from_rgba(0.95, 0.35, 0.1, 1.0))
+    }
+
+    fn setup(entity: &mut EntityCommands) {
+        entity.insert(Flammable);
+    }
+}
+
+fn blown_up_incendiaries(
+    mut commands: Commands,
+    mut explosions: EventReader<ExplosionEvent>,
+    incendiaries: Query<(Entity, &Transform2D, &TrapState), With<Incendiary>>,
+) {
+    for explosion in explosions.iter() {
+        for (ent, transform, state) in incendiaries.iter() {
+            if *state == TrapState::Placed && explosion.reaches(transform.origin) {
+                ignite(&mut commands, ent);
+            }
+        }
+    }
+}
+
+// A burning incendiary sets everything around it on fire, including zombies and the player
+fn process_incendiaries(
+    mut commands: Commands,
+    mut incendiaries: Query<
+        (Entity, &mut TrapState, &Transform2D),
+        (With<Incendiary>, Added<Burning>),
+    >,
+    targets: Query<(Entity, &Transform2D), (Or<(With<Hp>, With<Flammable>)>, Without<InShelter>)>,
+) {
+    for (incendiary, mut state, transform) in incendiaries.iter_mut() {
+        info!("Incendiary device went up in flames");
+        *state = TrapState::Triggered;
+
+        for (ent, target_transform) in targets.iter() {
+            if ent != incendiary
+                && target_transform.origin.distance_to(transform.origin) < FLAME_RADIUS
+            {
+                ignite(&mut commands, ent);
+            }
+        }
+    }
+}
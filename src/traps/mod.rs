@@ -1,12 +1,283 @@
-use bevy_godot::prelude::*;
+use crate::{
+    crafting::{CraftingAssets, Item},
+    ground_items::GroundItem,
+    map::MapTarget,
+    placement::AddPlaceable,
+    player::{Player, PlayerInteractVolume},
+    ui::{offscreen_indicator::OffscreenTarget, text_log::ItemLogEvent},
+    GameState,
+};
+use bevy::{ecs::system::EntityCommands, log::*};
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, Changed, EventWriter, With, Without},
+    godot_prelude::{Color, Vector2},
+    *,
+};
+use iyes_loopless::prelude::*;
+use std::{collections::HashMap, time::Duration};
 
 pub mod alarm;
-mod prox_bomb;
+pub mod explosion;
+pub mod fire;
+pub mod incendiary;
+pub mod prox_bomb;
+pub mod sentry;
+pub mod turret;
+
+// How close the player has to be to pick up a trap
+const PICK_UP_DISTANCE: f32 = 80.0;
 
 pub struct TrapsPlugin;
 impl Plugin for TrapsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(prox_bomb::ProximityBombPlugin)
-            .add_plugin(alarm::AlarmPlugin);
+        app.add_plugin(explosion::ExplosionPlugin)
+            .add_plugin(fire::FirePlugin)
+            .add_plugin(incendiary::IncendiaryPlugin)
+            .add_plugin(prox_bomb::ProximityBombPlugin)
+            .add_plugin(alarm::AlarmPlugin)
+            .add_plugin(sentry::SentryTowerPlugin)
+            .add_plugin(turret::DefenseTurretPlugin)
+            .add_system(despawn_expired_traps)
+            .add_system(pick_up_trap.run_in_state(GameState::Playing))
+            .add_exit_system(GameState::GameOver, on_restart);
+    }
+}
+
+// A device the player crafts and places in the desert. Register it with `App::add_trap` and the
+// labelling, node bindings, markers and clean up are taken care of. The trap's own plugin only
+// needs to add the systems that make it tick.
+pub trait Trap: Component + Default {
+    // What the player crafts to place this trap
+    const ITEM: Item;
+    // Where the trap's scene lives
+    const SCENE: &'static str;
+    // Group of the scene's root node
+    const GROUP: &'static str;
+    // Child nodes the trap works with, by their path in the scene
+    const NODES: &'static [&'static str] = &[];
+
+    // Traps with a color get an off-screen indicator
+    fn indicator_color() -> Option<Color> {
+        None
+    }
+
+    // Traps with a color are marked on the map
+    fn map_color() -> Option<Color> {
+        None
+    }
+
+    // Adds what the placed trap needs on top of what it gets labelled with
+    fn setup(_entity: &mut EntityCommands) {}
+
+    // Whether the player can take the trap back. Only traps that weren't set off by default.
+    fn can_pick_up(&self, state: TrapState) -> bool {
+        state == TrapState::Placed
+    }
+}
+
+// Where a trap is in its life. Expired traps are removed from the desert.
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq)]
+pub enum TrapState {
+    Placed,
+    Triggered,
+    Expired,
+}
+
+// What a trap in the desert was made from
+#[derive(Debug, Component)]
+pub struct PlacedTrap {
+    pub item: Item,
+    can_pick_up: bool,
+}
+
+// What's left of the battery of a device that was placed before
+#[derive(Debug, Component)]
+pub struct UsedBattery(pub Duration);
+
+// Entities of the trap's child nodes, by their path in the scene
+#[derive(Debug, Component, Default)]
+pub struct TrapNodes(HashMap<&'static str, Entity>);
+
+impl TrapNodes {
+    pub fn get(&self, path: &str) -> Entity {
+        *self
+            .0
+            .get(path)
+            .unwrap_or_else(|| panic!("trap has no node bound at {}", path))
+    }
+}
+
+pub trait AddTrap {
+    fn add_trap<T: Trap>(&mut self) -> &mut Self;
+}
+
+impl AddTrap for App {
+    fn add_trap<T: Trap>(&mut self) -> &mut Self {
+        self.add_placeable(T::ITEM, T::SCENE, T::setup)
+            .add_system(label_traps::<T>.run_not_in_state(GameState::Loading))
+            .add_system(update_pick_up::<T>)
+    }
+}
+
+fn label_traps<T: Trap>(
+    mut commands: Commands,
+    mut entities: Query<(&Groups, Entity, &mut ErasedGodotRef), Added<ErasedGodotRef>>,
+    crafting_assets: Res<CraftingAssets>,
+) {
+    let mut bindings = vec![];
+    for (groups, ent, mut reference) in entities.iter_mut() {
+        if !groups.is(T::GROUP) {
+            continue;
+        }
+
+        debug!("Placed {:?}", T::ITEM);
+        commands
+            .entity(ent)
+            .insert(T::default())
+            .insert(TrapState::Placed)
+            .insert(PlacedTrap {
+                item: T::ITEM,
+                can_pick_up: false,
+            });
+
+        if let Some(color) = T::indicator_color() {
+            commands.entity(ent).insert(OffscreenTarget {
+                icon: T::ITEM.as_texture_handle(&crafting_assets).clone(),
+                color,
+                tooltip: T::ITEM.as_str().to_string(),
+            });
+        }
+
+        if let Some(color) = T::map_color() {
+            commands.entity(ent).insert(MapTarget {
+                icon: T::ITEM.as_texture_handle(&crafting_assets).clone(),
+                color,
+                tooltip: T::ITEM.as_str().to_string(),
+            });
+        }
+
+        let node_instance_ids = T::NODES
+            .iter()
+            .map(|path| {
+                let node = reference.get::<Node>().get_node(*path).ok_or(*path)?;
+                Ok((*path, unsafe { node.assume_safe() }.get_instance_id()))
+            })
+            .collect::<Result<Vec<_>, &str>>();
+
+        match node_instance_ids {
+            Ok(node_instance_ids) => bindings.push((ent, node_instance_ids)),
+            Err(path) => error!("{} trap has no node at {}", T::GROUP, path),
+        }
+    }
+
+    for (trap, node_instance_ids) in bindings {
+        let nodes = node_instance_ids
+            .into_iter()
+            .map(|(path, instance_id)| {
+                let node = entities
+                    .iter()
+                    .find_map(|(_, ent, reference)| {
+                        (reference.instance_id() == instance_id).then_some(ent)
+                    })
+                    .ok_or(path)?;
+                Ok((path, node))
+            })
+            .collect::<Result<HashMap<_, _>, &str>>();
+
+        match nodes {
+            Ok(nodes) => {
+                commands.entity(trap).insert(TrapNodes(nodes));
+            }
+            Err(path) => error!("{} trap's node at {} has no entity", T::GROUP, path),
+        }
+    }
+}
+
+// Keeps track of which traps the player can take back
+fn update_pick_up<T: Trap>(mut traps: Query<(&T, &TrapState, &mut PlacedTrap)>) {
+    for (trap, state, mut placed) in traps.iter_mut() {
+        let can_pick_up = trap.can_pick_up(*state);
+        if placed.can_pick_up != can_pick_up {
+            placed.can_pick_up = can_pick_up;
+        }
+    }
+}
+
+fn despawn_expired_traps(mut traps: Query<(&TrapState, &mut ErasedGodotRef), Changed<TrapState>>) {
+    for (state, mut reference) in traps.iter_mut() {
+        if *state == TrapState::Expired {
+            reference.get::<Node>().queue_free();
+        }
+    }
+}
+
+// Takes the closest trap back into the inventory
+fn pick_up_trap(
+    mut traps: Query<
+        (
+            &PlacedTrap,
+            &TrapState,
+            &Transform2D,
+            Option<&alarm::Alarm>,
+            &mut ErasedGodotRef,
+        ),
+        Without<Player>,
+    >,
+    mut player: Query<(&mut Player, &Transform2D)>,
+    player_interact_volume: Query<&Collisions, With<PlayerInteractVolume>>,
+    ground_items: Query<(), With<GroundItem>>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
+
+    if !input.is_action_just_pressed("pick_up", false) {
+        return;
+    }
+
+    // things lying on the ground are picked up first
+    if player_interact_volume
+        .single()
+        .colliding()
+        .iter()
+        .any(|ent| ground_items.get(*ent).is_ok())
+    {
+        return;
+    }
+
+    let (mut player, player_transform) = player.single_mut();
+    let distance = |origin: Vector2| origin.distance_to(player_transform.origin);
+
+    let trap = traps
+        .iter_mut()
+        .filter(|(_, _, transform, _, _)| distance(transform.origin) < PICK_UP_DISTANCE)
+        .min_by(|(_, _, a, _, _), (_, _, b, _, _)| {
+            distance(a.origin).total_cmp(&distance(b.origin))
+        });
+
+    let (trap, state, _, alarm, mut reference) = match trap {
+        Some(trap) => trap,
+        None => return,
+    };
+
+    if !trap.can_pick_up {
+        item_log.send(ItemLogEvent(format!(
+            "The {} can't be picked up now",
+            trap.item.as_str()
+        )));
+        return;
+    }
+
+    debug!("Picking up {:?}", trap.item);
+    reference.get::<Node>().queue_free();
+    player
+        .inventory
+        .add_used_item(trap.item, alarm.map(alarm::Alarm::remaining_battery));
+
+    item_log.send(ItemLogEvent(format!("Picked up a {}", trap.item.as_str())));
+}
+
+fn on_restart(mut traps: Query<&mut ErasedGodotRef, With<TrapState>>) {
+    for mut trap in traps.iter_mut() {
+        trap.get::<Node>().queue_free();
     }
 }
@@ -1,145 +1,321 @@
-use crate::{
-    airdrops::{AirDrop, BonusAirDrop},
-    crafting::Part,
-    player::Player,
-    ui::text_log::ItemLogEvent,
-    Hp,
+use super::{
+    explosion::{Blast, ExplosionEvent},
+    AddTrap, Trap, TrapNodes, TrapState,
 };
+use crate::{crafting::Item, ui::text_log::ItemLogEvent, zombies::Zombie, GameState};
 use bevy::log::*;
 use bevy_godot::prelude::{
-    bevy_prelude::{Added, EventWriter, With, Without},
-    godot_prelude::Vector2,
+    bevy_prelude::{Added, EventReader, EventWriter, With, Without},
+    godot_prelude::Color,
     *,
 };
-use std::{f32::consts::PI, iter};
+use iyes_loopless::prelude::*;
+
+// Time from placing a bomb until it reacts to anything, so the player can walk away
+const ARMING_DELAY: f32 = 3.0;
+// Time from something walking in until the bang
+const DETONATE_DELAY: f32 = 2.0;
+// Bombs caught in another explosion go off a lot quicker
+const CHAIN_REACTION_DELAY: f32 = 0.4;
+// The horde sizes G cycles through, from a small pack to a crowd
+const HORDE_SIZES: &[usize] = &[3, 5, 8];
+// How fast the light of an armed bomb blinks (in seconds per blink)
+const BLINK_PERIOD: f32 = 0.8;
 
 pub struct ProximityBombPlugin;
 impl Plugin for ProximityBombPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(label_proximity_bombs)
-            .add_system(process_proximity_bombs);
+        app.init_resource::<TriggerFilter>()
+            .add_trap::<ProximityBomb>()
+            .add_trap::<FragmentationBomb>()
+            .add_system(cycle_trigger_filter.run_in_state(GameState::Playing))
+            .add_system(apply_trigger_filter::<ProximityBomb>)
+            .add_system(apply_trigger_filter::<FragmentationBomb>)
+            .add_system(process_proximity_bombs::<ProximityBomb>)
+            .add_system(process_proximity_bombs::<FragmentationBomb>)
+            .add_system(chain_reaction::<ProximityBomb>)
+            .add_system(chain_reaction::<FragmentationBomb>)
+            .add_system(blink_armed_bombs::<ProximityBomb>.as_visual_system())
+            .add_system(blink_armed_bombs::<FragmentationBomb>.as_visual_system());
+    }
+}
+
+// A trap that goes off shortly after something walks into it
+pub trait Bomb: Trap {
+    const BLAST: Blast;
+    // Shrapnel flies further than the blast and doesn't care who it hits
+    const FRAGMENTS: Option<Blast> = None;
+
+    fn proximity(&self) -> &ProximityBomb;
+    fn proximity_bomb(&mut self) -> &mut ProximityBomb;
+}
+
+// What has to walk into a bomb to set it off. Bombs keep the filter they were placed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TriggerFilter {
+    #[default]
+    Zombies,
+    // Waits for this many zombies at once, so a single one wandering by doesn't waste the bomb
+    Horde(usize),
+    // Anything that moves, the player included
+    AnyBody,
+}
+
+impl TriggerFilter {
+    fn next(self) -> Self {
+        match self {
+            TriggerFilter::Zombies => TriggerFilter::Horde(HORDE_SIZES[0]),
+            TriggerFilter::Horde(size) => HORDE_SIZES
+                .iter()
+                .find(|next| **next > size)
+                .map_or(TriggerFilter::AnyBody, |next| TriggerFilter::Horde(*next)),
+            TriggerFilter::AnyBody => TriggerFilter::Zombies,
+        }
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            TriggerFilter::Zombies => "any zombie".to_string(),
+            TriggerFilter::Horde(size) => format!("a horde of {size} zombies"),
+            TriggerFilter::AnyBody => "anything that moves".to_string(),
+        }
+    }
+
+    fn is_met(self, bodies: usize, zombies: usize) -> bool {
+        match self {
+            TriggerFilter::Zombies => zombies > 0,
+            TriggerFilter::Horde(size) => zombies >= size,
+            TriggerFilter::AnyBody => bodies > 0,
+        }
     }
 }
 
 #[derive(Debug, Component)]
 pub struct ProximityBomb {
+    arming_timer: Timer,
+    filter: TriggerFilter,
     detonate_timer: Option<Timer>,
     lifetime_timer: Option<Timer>,
 }
 
-#[derive(Debug, Component)]
-pub struct ProximityBombAnimationPlayer(Entity);
+impl Default for ProximityBomb {
+    fn default() -> Self {
+        Self {
+            arming_timer: Timer::from_seconds(ARMING_DELAY, false),
+            filter: TriggerFilter::default(),
+            detonate_timer: None,
+            lifetime_timer: None,
+        }
+    }
+}
+
+impl ProximityBomb {
+    // Whether the bomb is done arming and reacts to things walking in
+    pub fn is_armed(&self) -> bool {
+        self.arming_timer.finished()
+    }
+
+    // Starts the countdown. The animation is sped up or slowed down to end with the bang.
+    fn trigger(&mut self, delay: f32, animation_player: &mut ErasedGodotRef) {
+        animation_player.get::<AnimationPlayer>().play(
+            "detonate",
+            -1.0,
+            f64::from(DETONATE_DELAY / delay),
+            false,
+        );
+
+        self.detonate_timer = Some(Timer::from_seconds(delay, false));
+        self.lifetime_timer = Some(Timer::from_seconds(delay + 1.0, false));
+    }
+}
+
+impl Trap for ProximityBomb {
+    const ITEM: Item = Item::ProximityBomb;
+    const SCENE: &'static str = "res://traps/ProximityBomb.tscn";
+    const GROUP: &'static str = "proximity_bomb";
+    const NODES: &'static [&'static str] = &["AnimationPlayer", "ArmedLight"];
+
+    fn map_color() -> Option<Color> {
+        Some(Color::from_rgba(0.9, 0.6, 0.2, 1.0))
+    }
+
+    // Only while it's still arming, after that it's too touchy to handle
+    fn can_pick_up(&self, state: TrapState) -> bool {
+        state == TrapState::Placed && !self.is_armed()
+    }
+}
+
+impl Bomb for ProximityBomb {
+    const BLAST: Blast = Blast {
+        radius: 300.0,
+        power: 30.0,
+        hurts_player: false,
+    };
+
+    fn proximity(&self) -> &ProximityBomb {
+        self
+    }
+
+    fn proximity_bomb(&mut self) -> &mut ProximityBomb {
+        self
+    }
+}
+
+// A proximity bomb wrapped in scrap metal
+#[derive(Debug, Component, Default)]
+pub struct FragmentationBomb(ProximityBomb);
 
-fn label_proximity_bombs(
-    mut commands: Commands,
-    mut entities: Query<(&Groups, &mut ErasedGodotRef, Entity), Added<ErasedGodotRef>>,
+impl Trap for FragmentationBomb {
+    const ITEM: Item = Item::FragmentationBomb;
+    const SCENE: &'static str = "res://traps/FragmentationBomb.tscn";
+    const GROUP: &'static str = "fragmentation_bomb";
+    const NODES: &'static [&'static str] = ProximityBomb::NODES;
+
+    fn map_color() -> Option<Color> {
+        Some(Color::from_rgba(0.9, 0.45, 0.2, 1.0))
+    }
+
+    fn can_pick_up(&self, state: TrapState) -> bool {
+        self.0.can_pick_up(state)
+    }
+}
+
+impl Bomb for FragmentationBomb {
+    const BLAST: Blast = ProximityBomb::BLAST;
+    const FRAGMENTS: Option<Blast> = Some(Blast {
+        radius: 700.0,
+        power: 20.0,
+        hurts_player: true,
+    });
+
+    fn proximity(&self) -> &ProximityBomb {
+        &self.0
+    }
+
+    fn proximity_bomb(&mut self) -> &mut ProximityBomb {
+        &mut self.0
+    }
+}
+
+// Picks what sets off the bombs placed from now on
+fn cycle_trigger_filter(
+    mut filter: ResMut<TriggerFilter>,
+    mut item_log: EventWriter<ItemLogEvent>,
 ) {
-    let mut animation_players = vec![];
-    for (groups, mut reference, ent) in entities.iter_mut() {
-        if groups.is("proximity_bomb") {
-            commands.entity(ent).insert(ProximityBomb {
-                detonate_timer: None,
-                lifetime_timer: None,
-            });
-
-            let animation_player = unsafe {
-                reference
-                    .get::<Node2D>()
-                    .get_node("AnimationPlayer")
-                    .unwrap()
-                    .assume_safe()
-                    .get_instance_id()
-            };
-
-            animation_players.push((ent, animation_player));
-        }
+    let input = Input::godot_singleton();
+
+    if input.is_action_just_pressed("cycle_trigger_filter", false) {
+        *filter = filter.next();
+        item_log.send(ItemLogEvent(format!(
+            "Bombs will go off for {}",
+            filter.describe()
+        )));
     }
+}
 
-    for (bomb, animation_player_instance_id) in animation_players {
-        let animation_player = entities
-            .iter_mut()
-            .find_map(|(_, reference, ent)| {
-                (reference.instance_id() == animation_player_instance_id).then_some(ent)
-            })
-            .unwrap();
-        commands
-            .entity(animation_player)
-            .insert(ProximityBombAnimationPlayer(bomb));
-    }
-}
-
-fn process_proximity_bombs(
-    mut commands: Commands,
-    mut bombs: Query<(&mut ProximityBomb, Entity, &Collisions, &mut ErasedGodotRef)>,
-    mut animation_player: Query<
-        (&ProximityBombAnimationPlayer, &mut ErasedGodotRef),
-        Without<ProximityBomb>,
-    >,
-    mut entities: Query<&mut Hp>,
-    player: Query<&Transform2D, With<Player>>,
+fn apply_trigger_filter<T: Bomb>(mut bombs: Query<&mut T, Added<T>>, filter: Res<TriggerFilter>) {
+    for mut bomb in bombs.iter_mut() {
+        bomb.proximity_bomb().filter = *filter;
+    }
+}
+
+fn process_proximity_bombs<T: Bomb>(
+    mut bombs: Query<(
+        &mut T,
+        &mut TrapState,
+        &TrapNodes,
+        &Collisions,
+        &Transform2D,
+    )>,
+    mut nodes: Query<&mut ErasedGodotRef, Without<T>>,
+    zombies: Query<(), With<Zombie>>,
+    mut explosions: EventWriter<ExplosionEvent>,
     mut time: SystemDelta,
-    mut log: EventWriter<ItemLogEvent>,
 ) {
     let delta = time.delta();
 
-    for (mut bomb, bomb_ent, collisions, mut reference) in bombs.iter_mut() {
+    for (mut bomb, mut state, bomb_nodes, collisions, transform) in bombs.iter_mut() {
+        let bomb = bomb.proximity_bomb();
+
         if let Some(bomb_timer) = bomb.detonate_timer.as_mut() {
             bomb_timer.tick(delta);
             if bomb_timer.just_finished() {
-                info!("proximity bomb went off");
-                let mut killed_zombies = 0;
-
-                for ent in collisions.colliding() {
-                    if let Ok(mut obj_hp) = entities.get_mut(*ent) {
-                        obj_hp.0 = 0.0;
-                        killed_zombies += 1;
-                    }
-                }
+                info!("{:?} went off", T::ITEM);
 
-                if killed_zombies > 5 {
-                    log.send(ItemLogEvent(format!(
-                        "{}x Killing Spree! An extra airdrop is on the way!",
-                        killed_zombies
-                    )));
-
-                    let mut airdrop_transform = *player.single();
-
-                    airdrop_transform.set_rotation(rand::random::<f32>() * 2.0 * PI);
-                    airdrop_transform.0 = airdrop_transform.translated(Vector2::UP * 1000.0);
-                    airdrop_transform.set_rotation(0.0);
-
-                    commands
-                        .spawn()
-                        .insert(GodotScene::from_path("res://Airdrop.tscn"))
-                        .insert(AirDrop(
-                            iter::repeat_with(|| Part::random())
-                                .take(5)
-                                .collect::<Vec<_>>(),
-                        ))
-                        .insert(BonusAirDrop)
-                        .insert(airdrop_transform);
+                explosions.send(T::BLAST.at(transform.origin));
+                if let Some(fragments) = T::FRAGMENTS {
+                    explosions.send(fragments.at(transform.origin));
                 }
             }
-        } else if !collisions.recent_collisions().is_empty() {
-            info!("proximity bomb is armed");
-
-            let mut animation_player = animation_player
-                .iter_mut()
-                .find_map(|(player, reference)| (player.0 == bomb_ent).then_some(reference))
-                .unwrap();
-            animation_player
-                .get::<AnimationPlayer>()
-                .play("detonate", -1.0, 1.0, false);
-
-            bomb.detonate_timer = Some(Timer::from_seconds(2.0, false));
-            bomb.lifetime_timer = Some(Timer::from_seconds(3.0, false));
+        } else if !bomb.is_armed() {
+            bomb.arming_timer.tick(delta);
+            if bomb.arming_timer.just_finished() {
+                debug!("{:?} is armed", T::ITEM);
+            }
+        } else {
+            let bodies = collisions.colliding();
+            let zombie_count = bodies
+                .iter()
+                .filter(|ent| zombies.get(**ent).is_ok())
+                .count();
+
+            if bomb.filter.is_met(bodies.len(), zombie_count) {
+                info!("{:?} was triggered", T::ITEM);
+                *state = TrapState::Triggered;
+
+                let mut animation_player =
+                    nodes.get_mut(bomb_nodes.get("AnimationPlayer")).unwrap();
+                bomb.trigger(DETONATE_DELAY, &mut animation_player);
+            }
         }
 
         if let Some(lifetime_timer) = bomb.lifetime_timer.as_mut() {
             lifetime_timer.tick(delta);
             if lifetime_timer.just_finished() {
-                reference.get::<Node2D>().queue_free();
+                *state = TrapState::Expired;
             }
         }
     }
 }
+
+// Bombs within reach of an explosion go off too
+fn chain_reaction<T: Bomb>(
+    mut explosions: EventReader<ExplosionEvent>,
+    mut bombs: Query<(&mut T, &mut TrapState, &TrapNodes, &Transform2D)>,
+    mut nodes: Query<&mut ErasedGodotRef, Without<T>>,
+) {
+    for explosion in explosions.iter() {
+        for (mut bomb, mut state, bomb_nodes, transform) in bombs.iter_mut() {
+            if *state != TrapState::Placed || !explosion.reaches(transform.origin) {
+                continue;
+            }
+
+            debug!("{:?} caught in a chain reaction", T::ITEM);
+            *state = TrapState::Triggered;
+
+            let mut animation_player = nodes.get_mut(bomb_nodes.get("AnimationPlayer")).unwrap();
+            bomb.proximity_bomb()
+                .trigger(CHAIN_REACTION_DELAY, &mut animation_player);
+        }
+    }
+}
+
+// Armed bombs blink so the player knows to keep away. Bombs still arming stay dark.
+fn blink_armed_bombs<T: Bomb>(
+    bombs: Query<(&T, &TrapState, &TrapNodes)>,
+    mut nodes: Query<&mut ErasedGodotRef, Without<T>>,
+    time: Res<Time>,
+) {
+    let blink_on = (time.seconds_since_startup() as f32 / BLINK_PERIOD).fract() < 0.5;
+
+    for (bomb, state, bomb_nodes) in bombs.iter() {
+        let lit = match state {
+            TrapState::Placed => bomb.proximity().is_armed() && blink_on,
+            TrapState::Triggered => true,
+            TrapState::Expired => false,
+        };
+
+        let mut light = nodes.get_mut(bomb_nodes.get("ArmedLight")).unwrap();
+        light.get::<Node2D>().set_visible(lit);
+    }
+}
@@ -0,0 +1,175 @@
+use super::{AddTrap, Trap, TrapState};
+use crate::{
+    crafting::Item,
+    map::MapTarget,
+    player::Player,
+    ui::{
+        compass::direction_name, minimap::RadarSource, offscreen_indicator::OffscreenTarget,
+        text_log::ItemLogEvent,
+    },
+    zombies::{zombies_within, Zombie},
+    GameState,
+};
+use bevy::log::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, EventWriter, With, Without},
+    godot_prelude::{Color, Vector2},
+    *,
+};
+use iyes_loopless::prelude::*;
+
+// Zombies closer than this to a sentry tower are spotted
+pub const DETECTION_RADIUS: f32 = 800.0;
+// How long a battery keeps a sentry tower running (in seconds)
+const BATTERY_LIFE: f32 = 240.0;
+
+pub struct SentryTowerPlugin;
+impl Plugin for SentryTowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_trap::<SentryTower>()
+            .add_system(power_up_sentry_towers)
+            .add_system(process_sentry_towers.run_in_state(GameState::Playing));
+    }
+}
+
+// Watches the desert around it and tells the player when zombies come close
+#[derive(Debug, Component)]
+pub struct SentryTower {
+    battery: Timer,
+    zombies_in_range: usize,
+}
+
+impl Default for SentryTower {
+    fn default() -> Self {
+        Self {
+            battery: Timer::from_seconds(BATTERY_LIFE, false),
+            zombies_in_range: 0,
+        }
+    }
+}
+
+impl SentryTower {
+    // How much battery is left, from 0 (dead) to 1 (fresh)
+    pub fn battery_level(&self) -> f32 {
+        1.0 - self.battery.percent()
+    }
+
+    pub fn alert_color(&self) -> Color {
+        if self.zombies_in_range > 0 {
+            Color::from_rgba(0.9, 0.2, 0.2, 1.0)
+        } else {
+            Color::from_rgba(0.4, 0.6, 0.9, 1.0)
+        }
+    }
+}
+
+impl Trap for SentryTower {
+    const ITEM: Item = Item::SentryTower;
+    const SCENE: &'static str = "res://traps/SentryTower.tscn";
+    const GROUP: &'static str = "sentry_tower";
+
+    fn indicator_color() -> Option<Color> {
+        Some(Color::from_rgba(0.4, 0.6, 0.9, 0.5))
+    }
+
+    fn map_color() -> Option<Color> {
+        Some(Color::from_rgba(0.4, 0.6, 0.9, 1.0))
+    }
+
+    // Bolted to the ground
+    fn can_pick_up(&self, _: TrapState) -> bool {
+        false
+    }
+}
+
+// A running sentry tower lets the minimap see zombies around it
+fn power_up_sentry_towers(mut commands: Commands, towers: Query<Entity, Added<SentryTower>>) {
+    for ent in towers.iter() {
+        commands.entity(ent).insert(RadarSource {
+            radius: DETECTION_RADIUS,
+        });
+    }
+}
+
+fn process_sentry_towers(
+    mut commands: Commands,
+    mut towers: Query<(
+        Entity,
+        &mut SentryTower,
+        &mut TrapState,
+        &Transform2D,
+        Option<&mut OffscreenTarget>,
+        Option<&mut MapTarget>,
+    )>,
+    zombies: Query<&Transform2D, (With<Zombie>, Without<SentryTower>)>,
+    player: Query<&Transform2D, (With<Player>, Without<SentryTower>)>,
+    mut time: SystemDelta,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let delta = time.delta();
+    let player = player.single();
+
+    for (ent, mut tower, mut state, transform, indicator, map_target) in towers.iter_mut() {
+        if *state == TrapState::Expired {
+            continue;
+        }
+
+        tower.battery.tick(delta);
+        if tower.battery.finished() {
+            info!("Sentry tower ran out of battery");
+            item_log.send(ItemLogEvent("A sentry tower went quiet".to_string()));
+            commands.entity(ent).remove::<RadarSource>();
+            *state = TrapState::Expired;
+            continue;
+        }
+
+        let spotted = zombies_within(zombies.iter(), transform.origin, DETECTION_RADIUS);
+
+        // only the first zombie coming into range sounds the alert
+        if tower.zombies_in_range == 0 && !spotted.is_empty() {
+            let center = spotted
+                .iter()
+                .fold(Vector2::ZERO, |sum, origin| sum + *origin)
+                / spotted.len() as f32;
+            let offset = center - player.origin;
+
+            item_log.send(ItemLogEvent(format!(
+                "Sentry tower: {} zombie(s) {:.0}m to the {}",
+                spotted.len(),
+                offset.length() / 8.0,
+                direction_name(offset),
+            )));
+        }
+
+        tower.zombies_in_range = spotted.len();
+        let alerted = if spotted.is_empty() {
+            TrapState::Placed
+        } else {
+            TrapState::Triggered
+        };
+        if *state != alerted {
+            *state = alerted;
+        }
+
+        if let Some(mut indicator) = indicator {
+            indicator.color = Color {
+                a: 0.5,
+                ..tower.alert_color()
+            };
+            indicator.tooltip = format!(
+                "Sentry tower ({} zombies, {:.0}% battery)",
+                spotted.len(),
+                tower.battery_level() * 100.0
+            );
+        }
+
+        // red on the map too while zombies are around
+        if let Some(mut map_target) = map_target {
+            map_target.color = tower.alert_color();
+            map_target.tooltip = match spotted.len() {
+                0 => "Sentry tower (all quiet)".to_string(),
+                zombies => format!("Sentry tower ({zombies} zombies around)"),
+            };
+        }
+    }
+}
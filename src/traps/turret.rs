@@ -0,0 +1,209 @@
+use super::{AddTrap, Trap, TrapNodes, TrapState};
+use crate::{
+    crafting::Item,
+    map::MapTarget,
+    player::{spawn_bullet, Player},
+    ui::{offscreen_indicator::OffscreenTarget, text_log::ItemLogEvent},
+    zombies::{nearest_zombie, NoiseEvent, Zombie},
+    GameState,
+};
+use bevy::log::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{EventWriter, With, Without},
+    godot_prelude::Color,
+    *,
+};
+use iyes_loopless::prelude::*;
+
+// Zombies closer than this get shot at
+const TURRET_RANGE: f32 = 500.0;
+// Minimal time between two shots
+const TURRET_COOLDOWN: f32 = 1.5;
+// Bolts a fresh turret comes loaded with, and how many fit in it
+const TURRET_CAPACITY: u32 = 12;
+// At this many bolts left the turret shows it's running low
+const LOW_AMMO: u32 = 3;
+// How close the player has to be to refill a turret
+const REFILL_DISTANCE: f32 = 100.0;
+// Noise level of a turret shot (in dB at 1m)
+const TURRET_SHOT_NOISE: f32 = 40.0;
+
+pub struct DefenseTurretPlugin;
+impl Plugin for DefenseTurretPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_trap::<DefenseTurret>()
+            .add_system(
+                process_defense_turrets
+                    .as_physics_system()
+                    .run_in_state(GameState::Playing),
+            )
+            .add_system(refill_turret.run_in_state(GameState::Playing))
+            .add_system(show_turret_ammo.as_visual_system());
+    }
+}
+
+// A sentry tower with a crossbow. Shoots zombies until it runs out of bolts.
+#[derive(Debug, Component)]
+pub struct DefenseTurret {
+    ammo: u32,
+    cooldown: Timer,
+}
+
+impl Default for DefenseTurret {
+    fn default() -> Self {
+        Self {
+            ammo: TURRET_CAPACITY,
+            cooldown: Timer::from_seconds(TURRET_COOLDOWN, false),
+        }
+    }
+}
+
+impl DefenseTurret {
+    pub fn is_low_on_ammo(&self) -> bool {
+        self.ammo <= LOW_AMMO
+    }
+}
+
+impl Trap for DefenseTurret {
+    const ITEM: Item = Item::DefenseTurret;
+    const SCENE: &'static str = "res://traps/DefenseTurret.tscn";
+    const GROUP: &'static str = "defense_turret";
+    const NODES: &'static [&'static str] = &["Icon", "AudioStreamPlayer2D"];
+
+    fn indicator_color() -> Option<Color> {
+        Some(Color::from_rgba(0.5, 0.8, 0.5, 0.5))
+    }
+
+    fn map_color() -> Option<Color> {
+        Some(Color::from_rgba(0.5, 0.8, 0.5, 1.0))
+    }
+
+    // Bolted to the ground
+    fn can_pick_up(&self, _: TrapState) -> bool {
+        false
+    }
+}
+
+fn process_defense_turrets(
+    mut commands: Commands,
+    mut turrets: Query<(
+        &mut DefenseTurret,
+        &mut TrapState,
+        &TrapNodes,
+        &mut Transform2D,
+    )>,
+    mut nodes: Query<&mut ErasedGodotRef, Without<DefenseTurret>>,
+    zombies: Query<&Transform2D, (With<Zombie>, Without<DefenseTurret>)>,
+    mut noise: EventWriter<NoiseEvent>,
+    mut time: SystemDelta,
+) {
+    let delta = time.delta();
+
+    for (mut turret, mut state, turret_nodes, mut transform) in turrets.iter_mut() {
+        turret.cooldown.tick(delta);
+        if !turret.cooldown.finished() || turret.ammo == 0 {
+            continue;
+        }
+
+        let target = match nearest_zombie(zombies.iter(), transform.origin, TURRET_RANGE) {
+            Some(target) => target,
+            None => continue,
+        };
+
+        // turn toward the zombie and shoot
+        let direction = target - transform.origin;
+        transform.set_rotation(direction.x.atan2(-direction.y));
+        spawn_bullet(&mut commands, *transform);
+
+        let mut audio = nodes
+            .get_mut(turret_nodes.get("AudioStreamPlayer2D"))
+            .unwrap();
+        audio.get::<AudioStreamPlayer2D>().play(0.0);
+        noise.send(NoiseEvent {
+            origin: transform.origin,
+            level: TURRET_SHOT_NOISE,
+        });
+
+        turret.ammo -= 1;
+        turret.cooldown.reset();
+        debug!("Turret fired, {} bolts left", turret.ammo);
+
+        if turret.ammo == 0 && *state != TrapState::Triggered {
+            *state = TrapState::Triggered;
+        }
+    }
+}
+
+// Moves bolts from the player's backpack into the nearest turret
+fn refill_turret(
+    mut turrets: Query<(&mut DefenseTurret, &mut TrapState, &Transform2D), Without<Player>>,
+    mut player: Query<(&mut Player, &Transform2D)>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
+
+    if !input.is_action_just_pressed("refill_turret", false) {
+        return;
+    }
+
+    let (mut player, player_transform) = player.single_mut();
+    let turret = turrets.iter_mut().find(|(_, _, transform)| {
+        transform.origin.distance_to(player_transform.origin) < REFILL_DISTANCE
+    });
+
+    let (mut turret, mut state, _) = match turret {
+        Some(turret) => turret,
+        None => return,
+    };
+
+    let bolts = player.ammo_count.min(TURRET_CAPACITY - turret.ammo);
+    if bolts == 0 {
+        item_log.send(ItemLogEvent("No bolts to spare for the turret".to_string()));
+        return;
+    }
+
+    player.ammo_count -= bolts;
+    turret.ammo += bolts;
+    if *state == TrapState::Triggered {
+        *state = TrapState::Placed;
+    }
+
+    item_log.send(ItemLogEvent(format!(
+        "Loaded {bolts} bolts into the turret"
+    )));
+}
+
+// Turrets running low on bolts turn red
+fn show_turret_ammo(
+    mut turrets: Query<(
+        &DefenseTurret,
+        &TrapNodes,
+        Option<&mut OffscreenTarget>,
+        Option<&mut MapTarget>,
+    )>,
+    mut nodes: Query<&mut ErasedGodotRef, Without<DefenseTurret>>,
+) {
+    for (turret, turret_nodes, indicator, map_target) in turrets.iter_mut() {
+        let color = if turret.is_low_on_ammo() {
+            Color::from_rgba(0.9, 0.3, 0.3, 1.0)
+        } else {
+            Color::from_rgba(1.0, 1.0, 1.0, 1.0)
+        };
+
+        let mut icon = nodes.get_mut(turret_nodes.get("Icon")).unwrap();
+        icon.get::<Node2D>().set_modulate(color);
+
+        if let Some(mut indicator) = indicator {
+            indicator.tooltip = format!("Defense turret ({} bolts)", turret.ammo);
+        }
+
+        if let Some(mut map_target) = map_target {
+            map_target.color = if turret.is_low_on_ammo() {
+                Color::from_rgba(0.9, 0.3, 0.3, 1.0)
+            } else {
+                Color::from_rgba(0.5, 0.8, 0.5, 1.0)
+            };
+            map_target.tooltip = format!("Defense turret ({} bolts)", turret.ammo);
+        }
+    }
+}
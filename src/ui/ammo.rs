@@ -1,6 +1,6 @@
-use crate::player::Player;
+use crate::player::{Player, ReloadTimer};
 use bevy_godot::prelude::{
-    bevy_prelude::{Changed, With},
+    bevy_prelude::{Changed, Or, With},
     *,
 };
 
@@ -26,12 +26,17 @@ fn label_ammo_ui(mut commands: Commands, entities: Query<(&Name, Entity)>) {
 
 fn update_ammo_ui(
     mut ammo_ui: Query<&mut ErasedGodotRef, With<AmmoLabel>>,
-    player: Query<&Player, Changed<Player>>,
+    player: Query<(&Player, &ReloadTimer), Or<(Changed<Player>, Changed<ReloadTimer>)>>,
 ) {
-    if let Ok(player) = player.get_single() {
+    if let Ok((player, reload_timer)) = player.get_single() {
         let mut ammo_ui = ammo_ui.single_mut();
-        ammo_ui
-            .get::<Label>()
-            .set_text(format!("Ammo Remaining: {}", player.ammo_count));
+
+        let text = if let Some(timer) = reload_timer.0.as_ref() {
+            format!("Reloading... {:.0}%", timer.percent() * 100.0)
+        } else {
+            format!("Bolts: {} / {}", player.clip_count, player.ammo_count)
+        };
+
+        ammo_ui.get::<Label>().set_text(text);
     }
 }
@@ -0,0 +1,192 @@
+use crate::{player::Player, waypoints::Waypoint, GameState};
+use bevy::log::*;
+use bevy_asset_loader::prelude::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, With, Without},
+    godot_prelude::Vector2,
+    *,
+};
+use iyes_loopless::prelude::*;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+// The compass shows what's this far (in radians) to either side of the player's heading
+const COMPASS_SPAN: f32 = FRAC_PI_2;
+
+pub struct CompassUiPlugin;
+impl Plugin for CompassUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(label_compass)
+            .add_system(label_compass_markers)
+            .add_system(
+                spawn_compass_markers
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .add_system(
+                update_compass
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .insert_resource(CompassMarkerPool(0));
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct CompassAssets {
+    #[asset(path = "CompassMarker.tscn")]
+    marker_scene: Handle<GodotResource>,
+}
+
+#[derive(Component)]
+struct Compass;
+
+// Shows a waypoint on the compass strip
+#[derive(Component)]
+struct CompassMarker;
+
+// How many compass markers were instanced so far
+struct CompassMarkerPool(usize);
+
+// Compass bearing of a direction, clockwise from north (up)
+fn bearing(direction: Vector2) -> f32 {
+    direction.x.atan2(-direction.y)
+}
+
+// Where a direction points, in the words of a compass rose, e.g. "north-east"
+pub fn direction_name(direction: Vector2) -> &'static str {
+    const NAMES: [&str; 8] = [
+        "north",
+        "north-east",
+        "east",
+        "south-east",
+        "south",
+        "south-west",
+        "west",
+        "north-west",
+    ];
+
+    let sector = (bearing(direction) / (TAU / 8.0)).round().rem_euclid(8.0) as usize;
+    NAMES[sector]
+}
+
+// The shortest turn from one bearing to another, between -PI and PI
+fn relative_bearing(from: f32, to: f32) -> f32 {
+    (to - from + PI).rem_euclid(TAU) - PI
+}
+
+fn label_compass(mut commands: Commands, entities: Query<(&Name, Entity)>) {
+    let ent = entities
+        .iter()
+        .find_map(|(name, ent)| (name.as_str() == "Compass").then_some(ent))
+        .unwrap();
+
+    commands.entity(ent).insert(Compass);
+}
+
+fn label_compass_markers(
+    mut commands: Commands,
+    entities: Query<(&Groups, Entity), (Added<ErasedGodotRef>, Without<CompassMarker>)>,
+) {
+    for (groups, ent) in entities.iter() {
+        if groups.is("compass_marker") {
+            commands.entity(ent).insert(CompassMarker);
+        }
+    }
+}
+
+// Grow the marker pool, so there is one marker per waypoint
+fn spawn_compass_markers(
+    mut compass: Query<&mut ErasedGodotRef, With<Compass>>,
+    waypoints: Query<(), With<Waypoint>>,
+    mut pool: ResMut<CompassMarkerPool>,
+    compass_assets: Res<CompassAssets>,
+    assets: Res<Assets<GodotResource>>,
+) {
+    let missing = waypoints.iter().count().saturating_sub(pool.0);
+    if missing == 0 {
+        return;
+    }
+
+    let mut compass = compass.single_mut();
+    let compass = compass.get::<Node>();
+
+    let scene = assets
+        .get(&compass_assets.marker_scene)
+        .unwrap()
+        .0
+        .clone()
+        .cast::<PackedScene>()
+        .unwrap();
+    let scene = unsafe { scene.assume_safe() };
+
+    for _ in 0..missing {
+        let marker = scene
+            .instance(PackedScene::GEN_EDIT_STATE_DISABLED)
+            .unwrap();
+        compass.add_child(marker, false);
+    }
+
+    debug!("Instanced {missing} compass markers");
+    pool.0 += missing;
+}
+
+fn update_compass(
+    mut compass: Query<&mut ErasedGodotRef, (With<Compass>, Without<CompassMarker>)>,
+    mut markers: Query<&mut ErasedGodotRef, (With<CompassMarker>, Without<Compass>)>,
+    waypoints: Query<&Transform2D, With<Waypoint>>,
+    player: Query<&Transform2D, With<Player>>,
+) {
+    let mut compass = compass.single_mut();
+    let compass = compass.get::<Control>();
+    let half_width = compass.size().x / 2.0;
+
+    let player = player.single();
+    let heading = bearing(player.basis_xform_inv(Vector2::UP));
+
+    // where on the strip something at the given bearing goes, if it's within the span
+    let strip_position = |target_bearing: f32| {
+        let relative = relative_bearing(heading, target_bearing);
+        (relative.abs() <= COMPASS_SPAN).then(|| half_width + relative / COMPASS_SPAN * half_width)
+    };
+
+    for (name, direction) in [
+        ("N", Vector2::UP),
+        ("E", Vector2::RIGHT),
+        ("S", Vector2::DOWN),
+        ("W", Vector2::LEFT),
+    ] {
+        let label = compass.get_node(name).unwrap();
+        let label = unsafe { label.assume_safe() }.cast::<Label>().unwrap();
+
+        match strip_position(bearing(direction)) {
+            Some(x) => {
+                label.set_position(Vector2::new(x - label.size().x / 2.0, 0.0), false);
+                label.set_visible(true);
+            }
+            None => label.set_visible(false),
+        }
+    }
+
+    let mut waypoints = waypoints.iter();
+    for mut marker in markers.iter_mut() {
+        let marker = marker.get::<Label>();
+
+        let waypoint = match waypoints.next() {
+            Some(waypoint) => waypoint,
+            None => {
+                marker.set_visible(false);
+                continue;
+            }
+        };
+
+        let offset = waypoint.origin - player.origin;
+        match strip_position(bearing(offset)) {
+            Some(x) => {
+                marker.set_position(Vector2::new(x - marker.size().x / 2.0, 16.0), false);
+                marker.set_text(format!("▼\n{:.0}m", offset.length() / 8.0));
+                marker.set_visible(true);
+            }
+            None => marker.set_visible(false),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    airdrops::{AirDrop, OpenedCrate},
+    crafting::Part,
+    player::Player,
+    GameState,
+};
+use bevy::log::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{EventReader, With, Without},
+    *,
+};
+use iyes_loopless::prelude::*;
+
+pub struct CrateInventoryUiPlugin;
+impl Plugin for CrateInventoryUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_crate_inventory_ui)
+            .add_system(listen_for_crate_ui_presses.run_in_state(GameState::Playing))
+            .add_system(
+                update_crate_inventory_ui
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            );
+    }
+}
+
+#[derive(Component)]
+struct CrateInventoryUi;
+
+// A button taking the part in the given crate slot into the backpack
+#[derive(Component)]
+struct CrateSlotButton(usize);
+
+// A button storing one part of the given kind from the backpack in the crate
+#[derive(Component)]
+struct StashButton(Part);
+
+fn setup_crate_inventory_ui(
+    mut commands: Commands,
+    mut entities: Query<(&Name, Entity, &mut ErasedGodotRef)>,
+    mut scene_tree: SceneTreeRef,
+) {
+    for (name, ent, mut reference) in entities.iter_mut() {
+        let name = name.as_str();
+
+        if name == "CrateInventoryUI" {
+            commands.entity(ent).insert(CrateInventoryUi);
+        } else if let Some(slot) = name.strip_prefix("CrateSlot") {
+            let slot = slot.parse::<usize>().unwrap() - 1;
+
+            connect_godot_signal(&mut reference, "pressed", &mut scene_tree);
+            commands.entity(ent).insert(CrateSlotButton(slot));
+        } else if let Some(part) = name.strip_prefix("Stash") {
+            let part = Part::from_str(part).expect("to get Part from node's name");
+
+            connect_godot_signal(&mut reference, "pressed", &mut scene_tree);
+            commands.entity(ent).insert(StashButton(part));
+        }
+    }
+}
+
+fn listen_for_crate_ui_presses(
+    mut events: EventReader<GodotSignal>,
+    mut player: Query<&mut Player>,
+    mut airdrops: Query<&mut AirDrop>,
+    opened_crate: Res<OpenedCrate>,
+) {
+    let mut player = player.single_mut();
+
+    for event in events.iter() {
+        if event.name() != "pressed" {
+            continue;
+        }
+
+        let mut air_drop = match opened_crate.0.and_then(|ent| airdrops.get_mut(ent).ok()) {
+            Some(air_drop) => air_drop,
+            None => continue,
+        };
+
+        let node_name = event.origin().get::<Node>().name().to_string();
+
+        if let Some(slot) = node_name.strip_prefix("CrateSlot") {
+            let slot = slot.parse::<usize>().unwrap() - 1;
+
+            if slot < air_drop.parts.len() {
+                let part = air_drop.parts.remove(slot);
+                debug!("taking {:?} from the crate", part);
+                player.inventory.add_part(part);
+            }
+        } else if let Some(part) = node_name.strip_prefix("Stash").and_then(Part::from_str) {
+            if !air_drop.is_full() && player.inventory.remove_part(part) {
+                debug!("storing {:?} in the crate", part);
+                air_drop.parts.push(part);
+            }
+        }
+    }
+}
+
+fn update_crate_inventory_ui(
+    mut screen: Query<
+        &mut ErasedGodotRef,
+        (
+            With<CrateInventoryUi>,
+            Without<CrateSlotButton>,
+            Without<StashButton>,
+        ),
+    >,
+    mut crate_slots: Query<(&CrateSlotButton, &mut ErasedGodotRef), Without<StashButton>>,
+    mut stash_buttons: Query<(&StashButton, &mut ErasedGodotRef), Without<CrateSlotButton>>,
+    opened_crate: Res<OpenedCrate>,
+    airdrops: Query<&AirDrop>,
+    player: Query<&Player>,
+) {
+    let mut screen = screen.single_mut();
+    let screen = screen.get::<Control>();
+
+    let air_drop = match opened_crate.0.and_then(|ent| airdrops.get(ent).ok()) {
+        Some(air_drop) => air_drop,
+        None => {
+            screen.set_visible(false);
+            return;
+        }
+    };
+    screen.set_visible(true);
+
+    for (slot, mut reference) in crate_slots.iter_mut() {
+        let button = reference.get::<Button>();
+
+        if let Some(part) = air_drop.parts.get(slot.0) {
+            button.set_text(format!("{:?}", part));
+            button.set_disabled(false);
+        } else {
+            button.set_text("(empty)");
+            button.set_disabled(true);
+        }
+    }
+
+    let player = player.single();
+    for (stash, mut reference) in stash_buttons.iter_mut() {
+        let button = reference.get::<Button>();
+
+        let count = player
+            .inventory
+            .get_parts()
+            .get(&stash.0)
+            .copied()
+            .unwrap_or_default();
+
+        button.set_text(format!("{:?} x{}", stash.0, count));
+        button.set_disabled(count == 0 || air_drop.is_full());
+    }
+}
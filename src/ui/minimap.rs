@@ -0,0 +1,220 @@
+use crate::{
+    map::{MapTarget, MapView},
+    player::Player,
+    zombies::Zombie,
+    GameState,
+};
+use bevy::log::*;
+use bevy_asset_loader::prelude::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, With, Without},
+    godot_prelude::{Color, Vector2},
+    *,
+};
+use iyes_loopless::prelude::*;
+use std::f32::consts::TAU;
+
+// Half the size of the minimap widget (in pixels)
+const MINIMAP_EXTENT: f32 = 80.0;
+
+pub struct MinimapUiPlugin;
+impl Plugin for MinimapUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(label_minimap)
+            .add_system(label_blips)
+            .add_system(
+                spawn_blips
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .add_system(
+                update_minimap
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .insert_resource(MinimapSettings::default())
+            .insert_resource(BlipPool::default());
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct MinimapAssets {
+    #[asset(path = "MinimapBlip.tscn")]
+    blip_scene: Handle<GodotResource>,
+}
+
+pub struct MinimapSettings {
+    // How far around the player the minimap reaches (in units)
+    pub radius: f32,
+    // Seconds for the radar sweep to go full circle
+    pub sweep_period: f32,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            radius: 1500.0,
+            sweep_period: 3.0,
+        }
+    }
+}
+
+// Zombies within the radius of an active radar source show up on the minimap
+#[derive(Component)]
+pub struct RadarSource {
+    pub radius: f32,
+}
+
+#[derive(Component)]
+struct Minimap;
+
+#[derive(Component)]
+struct MinimapBlips;
+
+#[derive(Component)]
+struct MinimapBlip;
+
+#[derive(Default)]
+struct BlipPool {
+    // How many blips were instanced so far
+    instanced: usize,
+    // How many blips were needed last frame
+    wanted: usize,
+}
+
+fn label_minimap(mut commands: Commands, entities: Query<(&Name, Entity)>) {
+    let minimap = entities
+        .iter()
+        .find_map(|(name, ent)| (name.as_str() == "Minimap").then_some(ent))
+        .unwrap();
+
+    commands.entity(minimap).insert(Minimap);
+
+    let blips = entities
+        .iter()
+        .find_map(|(name, ent)| (name.as_str() == "MinimapBlips").then_some(ent))
+        .unwrap();
+
+    commands.entity(blips).insert(MinimapBlips);
+}
+
+fn label_blips(
+    mut commands: Commands,
+    entities: Query<(&Groups, Entity), (Added<ErasedGodotRef>, Without<MinimapBlip>)>,
+) {
+    for (groups, ent) in entities.iter() {
+        if groups.is("minimap_blip") {
+            commands.entity(ent).insert(MinimapBlip);
+        }
+    }
+}
+
+// Grow the blip pool, so there are enough blips for everything in range
+fn spawn_blips(
+    mut container: Query<&mut ErasedGodotRef, With<MinimapBlips>>,
+    mut pool: ResMut<BlipPool>,
+    minimap_assets: Res<MinimapAssets>,
+    assets: Res<Assets<GodotResource>>,
+) {
+    let missing = pool.wanted.saturating_sub(pool.instanced);
+    if missing == 0 {
+        return;
+    }
+
+    let mut container = container.single_mut();
+    let container = container.get::<Node>();
+
+    let scene = assets
+        .get(&minimap_assets.blip_scene)
+        .unwrap()
+        .0
+        .clone()
+        .cast::<PackedScene>()
+        .unwrap();
+    let scene = unsafe { scene.assume_safe() };
+
+    for _ in 0..missing {
+        let blip = scene
+            .instance(PackedScene::GEN_EDIT_STATE_DISABLED)
+            .unwrap();
+        container.add_child(blip, false);
+    }
+
+    debug!("Instanced {missing} minimap blips");
+    pool.instanced += missing;
+}
+
+fn update_minimap(
+    mut minimap: Query<&mut ErasedGodotRef, (With<Minimap>, Without<MinimapBlip>)>,
+    mut blips: Query<
+        (&mut Transform2D, &mut ErasedGodotRef),
+        (With<MinimapBlip>, Without<Minimap>),
+    >,
+    targets: Query<(&MapTarget, &Transform2D), (Without<Player>, Without<MinimapBlip>)>,
+    zombies: Query<&Transform2D, (With<Zombie>, Without<MinimapBlip>)>,
+    radars: Query<(&RadarSource, &Transform2D), Without<MinimapBlip>>,
+    player: Query<&Transform2D, (With<Player>, Without<MinimapBlip>)>,
+    mut pool: ResMut<BlipPool>,
+    settings: Res<MinimapSettings>,
+    map: Res<MapView>,
+    time: Res<Time>,
+) {
+    let mut minimap = minimap.single_mut();
+    let minimap = minimap.get::<Control>();
+
+    // the big map shows it all anyway
+    minimap.set_visible(!map.0);
+
+    let rect = minimap.get_global_rect();
+    let center = rect.position + rect.size / 2.0;
+    let player = player.single().origin;
+
+    let sweep_angle =
+        TAU * (time.seconds_since_startup() as f32 % settings.sweep_period) / settings.sweep_period;
+    let sweep = minimap.get_node("Sweep").unwrap();
+    let sweep = unsafe { sweep.assume_safe() }.cast::<Control>().unwrap();
+    sweep.set_rotation(sweep_angle as f64);
+
+    let in_range = |origin: Vector2| origin.distance_to(player) < settings.radius;
+
+    let mut wanted = targets
+        .iter()
+        .filter(|(_, transform)| in_range(transform.origin))
+        .map(|(target, transform)| (transform.origin, target.color))
+        .collect::<Vec<_>>();
+
+    // zombies are only known about when a radar picks them up
+    for zombie in zombies.iter() {
+        let detected = radars
+            .iter()
+            .any(|(radar, transform)| transform.origin.distance_to(zombie.origin) < radar.radius);
+        if !detected || !in_range(zombie.origin) {
+            continue;
+        }
+
+        // blips fade out until the sweep comes around again
+        let offset = zombie.origin - player;
+        let angle = offset.x.atan2(-offset.y);
+        let since_sweep = (sweep_angle - angle).rem_euclid(TAU);
+        let alpha = 1.0 - since_sweep / TAU;
+
+        wanted.push((zombie.origin, Color::from_rgba(0.9, 0.2, 0.2, alpha)));
+    }
+
+    pool.wanted = wanted.len();
+
+    let mut wanted = wanted.into_iter();
+    for (mut blip_transform, mut blip_reference) in blips.iter_mut() {
+        let blip = blip_reference.get::<Node2D>();
+
+        match wanted.next() {
+            Some((origin, color)) if !map.0 => {
+                let position = center + (origin - player) / settings.radius * MINIMAP_EXTENT;
+                blip_transform.0 = GodotTransform2D::IDENTITY.translated(position);
+                blip.set_modulate(color);
+                blip.set_visible(true);
+            }
+            _ => blip.set_visible(false),
+        }
+    }
+}
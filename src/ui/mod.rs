@@ -1,8 +1,12 @@
 use bevy_godot::prelude::*;
 
 mod ammo;
+pub mod compass;
+mod crate_inventory;
 mod game_over;
 mod item_bar;
+pub mod minimap;
+pub mod offscreen_indicator;
 mod score;
 mod shelter;
 mod stamina;
@@ -17,6 +21,10 @@ impl Plugin for UiPlugin {
             .add_plugin(item_bar::ItemBarUiPlugin)
             .add_plugin(score::ScoreUiPlugin)
             .add_plugin(ammo::AmmoUiPlugin)
+            .add_plugin(offscreen_indicator::OffscreenIndicatorPlugin)
+            .add_plugin(minimap::MinimapUiPlugin)
+            .add_plugin(compass::CompassUiPlugin)
+            .add_plugin(crate_inventory::CrateInventoryUiPlugin)
             .add_plugin(stamina::StaminaUiPlugin)
             .add_plugin(text_log::ItemLogPlugin);
     }
@@ -0,0 +1,267 @@
+use crate::{player::Player, GameState};
+use bevy::log::*;
+use bevy_asset_loader::prelude::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{Added, With, Without},
+    godot_prelude::{Color, Rect2, Vector2},
+    *,
+};
+use iyes_loopless::prelude::*;
+
+// How far from the screen edge the indicators are kept
+const EDGE_MARGIN: f32 = 40.0;
+
+pub struct OffscreenIndicatorPlugin;
+impl Plugin for OffscreenIndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(label_indicator_container)
+            .add_system(label_indicators)
+            .add_system(
+                spawn_indicators
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .add_system(
+                update_indicators
+                    .as_visual_system()
+                    .run_not_in_state(GameState::Loading),
+            )
+            .insert_resource(IndicatorPool(0));
+    }
+}
+
+#[derive(AssetCollection)]
+pub struct OffscreenIndicatorAssets {
+    #[asset(path = "OffscreenIndicator.tscn")]
+    indicator_scene: Handle<GodotResource>,
+}
+
+// Any entity with this component gets an arrow on the screen edge pointing at it while it's
+// out of sight.
+#[derive(Component)]
+pub struct OffscreenTarget {
+    pub icon: Handle<GodotResource>,
+    pub color: Color,
+    pub tooltip: String,
+}
+
+// Points at an off-screen target. Indicators are pooled and reassigned as targets come and go.
+#[derive(Component)]
+pub struct OffscreenIndicator(Option<Entity>);
+
+// The node holding all the indicators
+#[derive(Component)]
+struct IndicatorContainer;
+
+// How many indicators were instanced so far
+struct IndicatorPool(usize);
+
+// Whether a point in screen coordinates is inside the viewport
+pub fn is_on_screen(viewport: Rect2, point: Vector2) -> bool {
+    let min = viewport.position;
+    let max = viewport.position + viewport.size;
+
+    point.x > min.x && point.x < max.x && point.y > min.y && point.y < max.y
+}
+
+// Where on the screen edge to put an indicator for a target, as seen from `from`. Both points are
+// in screen coordinates. Returns `None` when the target is on the screen.
+pub fn project_to_screen_edge(
+    viewport: Rect2,
+    from: Vector2,
+    target: Vector2,
+    margin: f32,
+) -> Option<Vector2> {
+    if is_on_screen(viewport, target) {
+        return None;
+    }
+
+    let min = viewport.position;
+    let max = viewport.position + viewport.size;
+
+    let inner_min = min + Vector2::new(margin, margin);
+    let inner_max = max - Vector2::new(margin, margin);
+    let from = Vector2::new(
+        from.x.clamp(inner_min.x, inner_max.x),
+        from.y.clamp(inner_min.y, inner_max.y),
+    );
+    let direction = target - from;
+
+    // how far along the ray each pair of edges is
+    let along_x = if direction.x > 0.0 {
+        (inner_max.x - from.x) / direction.x
+    } else if direction.x < 0.0 {
+        (inner_min.x - from.x) / direction.x
+    } else {
+        f32::INFINITY
+    };
+    let along_y = if direction.y > 0.0 {
+        (inner_max.y - from.y) / direction.y
+    } else if direction.y < 0.0 {
+        (inner_min.y - from.y) / direction.y
+    } else {
+        f32::INFINITY
+    };
+
+    Some(from + direction * along_x.min(along_y).max(0.0))
+}
+
+fn label_indicator_container(mut commands: Commands, entities: Query<(&Name, Entity)>) {
+    let ent = entities
+        .iter()
+        .find_map(|(name, ent)| (name.as_str() == "OffscreenIndicators").then_some(ent))
+        .unwrap();
+
+    commands.entity(ent).insert(IndicatorContainer);
+}
+
+fn label_indicators(
+    mut commands: Commands,
+    entities: Query<(&Groups, Entity), (Added<ErasedGodotRef>, Without<OffscreenIndicator>)>,
+) {
+    for (groups, ent) in entities.iter() {
+        if groups.is("offscreen_indicator") {
+            commands.entity(ent).insert(OffscreenIndicator(None));
+        }
+    }
+}
+
+// Grow the indicator pool, so there is one indicator per target
+fn spawn_indicators(
+    mut container: Query<&mut ErasedGodotRef, With<IndicatorContainer>>,
+    targets: Query<(), With<OffscreenTarget>>,
+    mut pool: ResMut<IndicatorPool>,
+    indicator_assets: Res<OffscreenIndicatorAssets>,
+    assets: Res<Assets<GodotResource>>,
+) {
+    let missing = targets.iter().count().saturating_sub(pool.0);
+    if missing == 0 {
+        return;
+    }
+
+    let mut container = container.single_mut();
+    let container = container.get::<Node>();
+
+    let scene = assets
+        .get(&indicator_assets.indicator_scene)
+        .unwrap()
+        .0
+        .clone()
+        .cast::<PackedScene>()
+        .unwrap();
+    let scene = unsafe { scene.assume_safe() };
+
+    for _ in 0..missing {
+        let indicator = scene
+            .instance(PackedScene::GEN_EDIT_STATE_DISABLED)
+            .unwrap();
+        container.add_child(indicator, false);
+    }
+
+    debug!("Instanced {missing} offscreen indicators");
+    pool.0 += missing;
+}
+
+fn update_indicators(
+    mut indicators: Query<
+        (
+            &mut OffscreenIndicator,
+            &mut Transform2D,
+            &mut ErasedGodotRef,
+        ),
+        (Without<OffscreenTarget>, Without<Player>),
+    >,
+    mut targets: Query<
+        (&OffscreenTarget, &Transform2D, &mut ErasedGodotRef, Entity),
+        (Without<OffscreenIndicator>, Without<Player>),
+    >,
+    mut player: Query<
+        (&Transform2D, &mut ErasedGodotRef),
+        (
+            With<Player>,
+            Without<OffscreenIndicator>,
+            Without<OffscreenTarget>,
+        ),
+    >,
+    assets: Res<Assets<GodotResource>>,
+) {
+    let (player_transform, mut player_reference) = player.single_mut();
+    let player_screen_origin = player_reference
+        .get::<Node2D>()
+        .get_global_transform_with_canvas()
+        .origin;
+
+    // hand out free indicators to the targets that don't have one yet
+    let mut unassigned = targets
+        .iter()
+        .map(|(_, _, _, ent)| ent)
+        .filter(|ent| {
+            !indicators
+                .iter()
+                .any(|(indicator, _, _)| indicator.0 == Some(*ent))
+        })
+        .collect::<Vec<_>>();
+
+    for (mut indicator, _, _) in indicators.iter_mut() {
+        if indicator.0.map_or(true, |ent| targets.get(ent).is_err()) {
+            indicator.0 = unassigned.pop();
+        }
+    }
+
+    for (indicator, mut indicator_transform, mut indicator_reference) in indicators.iter_mut() {
+        let indicator_node = indicator_reference.get::<Node2D>();
+
+        let (target, target_transform, mut target_reference, _) =
+            match indicator.0.and_then(|ent| targets.get_mut(ent).ok()) {
+                Some(target) => target,
+                None => {
+                    indicator_node.set_visible(false);
+                    continue;
+                }
+            };
+
+        let target_screen_origin = target_reference
+            .get::<Node2D>()
+            .get_global_transform_with_canvas()
+            .origin;
+
+        let edge_origin = project_to_screen_edge(
+            indicator_node.get_viewport_rect(),
+            player_screen_origin,
+            target_screen_origin,
+            EDGE_MARGIN,
+        );
+
+        let origin = match edge_origin {
+            Some(origin) => origin,
+            None => {
+                indicator_node.set_visible(false);
+                continue;
+            }
+        };
+
+        indicator_transform.0 = GodotTransform2D::IDENTITY.translated(origin);
+        indicator_node.set_visible(true);
+
+        let direction = (target_screen_origin - origin).normalized();
+
+        let arrow = indicator_node.get_node("Arrow").unwrap();
+        let arrow = unsafe { arrow.assume_safe() }.cast::<Node2D>().unwrap();
+        arrow.set_position(direction * 28.0);
+        arrow.set_rotation(direction.x.atan2(-direction.y) as f64);
+
+        let icon = indicator_node.get_node("Icon").unwrap();
+        let icon = unsafe { icon.assume_safe() }.cast::<TextureRect>().unwrap();
+        let texture = assets.get(&target.icon).unwrap();
+        icon.set_texture(texture.0.clone().cast::<Texture>().unwrap());
+        icon.set_modulate(target.color);
+        icon.set_tooltip(target.tooltip.as_str());
+
+        // keep the distance label on the inner side of the indicator
+        let label = indicator_node.get_node("Control/Distance").unwrap();
+        let label = unsafe { label.assume_safe() }.cast::<Label>().unwrap();
+        let distance = target_transform.origin.distance_to(player_transform.origin);
+        label.set_position(-direction * EDGE_MARGIN, false);
+        label.set_text(format!("{:.0}m", distance / 8.0));
+    }
+}
@@ -18,7 +18,6 @@ pub struct ShelterUiPlugin;
 impl Plugin for ShelterUiPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(setup_shelter_ui)
-            .add_system(debug_toggle_shelter_mode.as_visual_system())
             .add_system(listen_for_crafting_ui_presses.run_in_state(GameState::Sheltered))
             .add_system(update_recipe_text)
             .add_system(update_recipe_preview.run_not_in_state(GameState::Loading))
@@ -230,22 +229,6 @@ fn listen_for_crafting_ui_presses(
     }
 }
 
-fn debug_toggle_shelter_mode(mut commands: Commands, state: Res<CurrentState<GameState>>) {
-    let input = Input::godot_singleton();
-
-    if input.is_action_just_pressed("debug_enter_shelter_mode", false) {
-        if state.0 == GameState::Playing {
-            commands.insert_resource(NextState(GameState::Sheltered));
-        } else if state.0 == GameState::Sheltered {
-            commands.insert_resource(NextState(GameState::Playing));
-        }
-    }
-
-    if input.is_action_just_pressed("ui_cancel", false) && state.0 == GameState::Sheltered {
-        commands.insert_resource(NextState(GameState::Playing));
-    }
-}
-
 fn update_recipe_text(
     recipe: Query<&CraftingTarget>,
     player: Query<&Player>,
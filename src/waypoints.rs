@@ -0,0 +1,71 @@
+use crate::{
+    player::{mouse_position, Player},
+    ui::text_log::ItemLogEvent,
+    GameState,
+};
+use bevy::log::*;
+use bevy_godot::prelude::{
+    bevy_prelude::{EventWriter, With, Without},
+    godot_prelude::Vector2,
+    *,
+};
+use iyes_loopless::prelude::*;
+
+// How close to a waypoint the mouse has to be to remove it
+const WAYPOINT_PICK_DISTANCE: f32 = 60.0;
+
+pub struct WaypointsPlugin;
+impl Plugin for WaypointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_waypoint.run_in_state(GameState::Playing))
+            .add_exit_system(GameState::GameOver, on_restart);
+    }
+}
+
+// A spot in the desert the player wants to come back to
+#[derive(Debug, Component)]
+pub struct Waypoint;
+
+// Places a waypoint under the mouse, or removes the one that's already there
+fn toggle_waypoint(
+    mut commands: Commands,
+    mut player: Query<&mut ErasedGodotRef, With<Player>>,
+    mut waypoints: Query<(&Transform2D, &mut ErasedGodotRef), (With<Waypoint>, Without<Player>)>,
+    mut item_log: EventWriter<ItemLogEvent>,
+) {
+    let input = Input::godot_singleton();
+
+    if !input.is_action_just_pressed("toggle_waypoint", false) {
+        return;
+    }
+
+    let mouse_position = mouse_position(player.single_mut().get::<Node2D>());
+
+    let existing = waypoints.iter_mut().find(|(transform, _)| {
+        transform.origin.distance_to(mouse_position) < WAYPOINT_PICK_DISTANCE
+    });
+
+    if let Some((_, mut reference)) = existing {
+        debug!("Removing waypoint at {mouse_position:?}");
+        reference.get::<Node>().queue_free();
+        item_log.send(ItemLogEvent("Removed a waypoint".to_string()));
+    } else {
+        debug!("Placing waypoint at {mouse_position:?}");
+        place_waypoint(&mut commands, mouse_position);
+        item_log.send(ItemLogEvent("Placed a waypoint".to_string()));
+    }
+}
+
+fn place_waypoint(commands: &mut Commands, origin: Vector2) {
+    commands
+        .spawn()
+        .insert(GodotScene::from_path("res://Waypoint.tscn"))
+        .insert(Waypoint)
+        .insert(Transform2D(GodotTransform2D::IDENTITY.translated(origin)));
+}
+
+fn on_restart(mut waypoints: Query<&mut ErasedGodotRef, With<Waypoint>>) {
+    for mut waypoint in waypoints.iter_mut() {
+        waypoint.get::<Node>().queue_free();
+    }
+}
@@ -1,7 +1,8 @@
 use std::f32::consts::PI;
 
 use crate::{
-    player::{Player, PlayerInteractVolume},
+    player::{Activity, Player, PlayerInteractVolume},
+    shelters::InShelter,
     traps::alarm::Alarm,
     GameState, Hp, RoundStart, Score,
 };
@@ -25,6 +26,8 @@ impl Plugin for ZombiesPlugin {
             .add_system(despawn_faraway_zombies.as_physics_system())
             .add_system(kill_zombies.as_physics_system())
             .add_system(zombie_targeting.as_physics_system())
+            .add_system(zombies_hear_noise.as_physics_system())
+            .add_event::<NoiseEvent>()
             .add_exit_system(GameState::GameOver, on_restart)
             .add_exit_system(GameState::GameOver, populate);
     }
@@ -41,6 +44,29 @@ pub struct Zombie;
 
 struct SpawnTimer(Timer);
 
+// Zombies can hear sounds louder than this (in dB)
+const HEARING_THRESHOLD: f32 = 5.0;
+// How far zombies notice the player and active alarms
+const SIGHT_DISTANCE: f32 = 750.0;
+// A ducking player is only noticed up close
+const DUCKING_SIGHT_DISTANCE: f32 = 300.0;
+
+// A noise made somewhere in the desert. The level is in dB at 1m distance (see DESIGN.md).
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseEvent {
+    pub origin: Vector2,
+    pub level: f32,
+}
+
+impl NoiseEvent {
+    // The sound level drops by 6 dB times the square root of the distance in meters, as in
+    // DESIGN.md. One meter is 8 units.
+    pub fn is_audible_from(&self, position: Vector2) -> bool {
+        let distance = self.origin.distance_to(position) / 8.0;
+        self.level - 6.0 * distance.sqrt() > HEARING_THRESHOLD
+    }
+}
+
 // A target represents a point where a zombie wants to be
 #[derive(Debug, Component)]
 pub struct Target(pub Vector2);
@@ -60,6 +86,30 @@ fn random_displacement(min_distance: u32, max_distance: u32) -> Vector2 {
     Vector2::UP.rotated(direction) * distance
 }
 
+// Positions of the zombies within the given distance of a point
+pub fn zombies_within<'a>(
+    zombies: impl IntoIterator<Item = &'a Transform2D>,
+    origin: Vector2,
+    distance: f32,
+) -> Vec<Vector2> {
+    zombies
+        .into_iter()
+        .map(|zombie| zombie.origin)
+        .filter(|zombie| zombie.distance_to(origin) < distance)
+        .collect()
+}
+
+// Position of the zombie closest to a point, if there is one within the given distance
+pub fn nearest_zombie<'a>(
+    zombies: impl IntoIterator<Item = &'a Transform2D>,
+    origin: Vector2,
+    distance: f32,
+) -> Option<Vector2> {
+    zombies_within(zombies, origin, distance)
+        .into_iter()
+        .min_by(|a, b| a.distance_to(origin).total_cmp(&b.distance_to(origin)))
+}
+
 fn populate(mut commands: Commands, player: Query<&Transform2D, With<Player>>) {
     let player_origin = player
         .get_single()
@@ -195,9 +245,16 @@ fn zombies_move(
 
 pub fn zombie_targeting(
     mut zombies: Query<(&Transform2D, &mut Target), With<Zombie>>,
-    player: Query<&Transform2D, With<Player>>,
+    player: Query<(&Transform2D, &Activity, Option<&InShelter>), With<Player>>,
     alarms: Query<(&Alarm, &Transform2D)>,
 ) {
+    let (player, activity, in_shelter) = player.single();
+    let player_sight_distance = if *activity == Activity::Ducking {
+        DUCKING_SIGHT_DISTANCE
+    } else {
+        SIGHT_DISTANCE
+    };
+
     for (zombie, mut target) in zombies.iter_mut() {
         let closest_alarm = alarms
             .iter()
@@ -210,13 +267,17 @@ pub fn zombie_targeting(
                     .unwrap()
             });
 
-        let player = player.single();
-
-        let closest_target = closest_alarm.unwrap_or(player.origin);
-
-        if zombie.origin.distance_to(closest_target) < 750.0 {
-            *target = Target(closest_target);
-        } else if zombie.origin.distance_to(player.origin) < 750.0 {
+        let closest_alarm =
+            closest_alarm.filter(|alarm| zombie.origin.distance_to(*alarm) < SIGHT_DISTANCE);
+
+        if let Some(alarm) = closest_alarm {
+            *target = Target(alarm);
+        } else if in_shelter.is_some() {
+            // the player is gone, those who were after them wander off
+            if target.0.distance_to(player.origin) < 200.0 {
+                *target = Target::random(zombie.origin);
+            }
+        } else if zombie.origin.distance_to(player.origin) < player_sight_distance {
             *target = Target(player.origin);
         } else if zombie.origin.distance_to(target.0) < 200.0 {
             *target = Target::random(zombie.origin);
@@ -224,6 +285,19 @@ pub fn zombie_targeting(
     }
 }
 
+fn zombies_hear_noise(
+    mut zombies: Query<(&Transform2D, &mut Target), With<Zombie>>,
+    mut noises: EventReader<NoiseEvent>,
+) {
+    for noise in noises.iter() {
+        for (zombie, mut target) in zombies.iter_mut() {
+            if noise.is_audible_from(zombie.origin) {
+                *target = Target(noise.origin);
+            }
+        }
+    }
+}
+
 fn kill_zombies(
     mut zombies: Query<(&Hp, &mut ErasedGodotRef), With<Zombie>>,
     mut score: ResMut<Score>,